@@ -0,0 +1,188 @@
+// Copyright Metatype OÜ, licensed under the Mozilla Public License Version 2.0.
+// SPDX-License-Identifier: MPL-2.0
+
+//! JSON Schema (draft 2020-12) of a single type, to validate payloads outside
+//! of the typegate. Named types are described in `$defs`, so that recursive
+//! types are supported; files and functions have no JSON Schema equivalent.
+
+use serde_json::{json, Value};
+
+use crate::conversion::types::DESCRIPTION_CONFIG_KEY;
+use crate::errors::{self, Result};
+use crate::types::{TypeDef, TypeDefExt, TypeId};
+
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+type Defs = serde_json::Map<String, Value>;
+
+impl TypeId {
+    pub fn to_json_schema(&self) -> Result<Value> {
+        let mut defs = Defs::new();
+        let (_, type_def) = self.resolve_ref()?;
+        let mut schema = def_schema(&type_def, &mut defs)?;
+        schema["$schema"] = json!(DIALECT);
+        if !defs.is_empty() {
+            schema["$defs"] = Value::Object(defs);
+        }
+        Ok(schema)
+    }
+}
+
+fn type_schema(type_id: TypeId, defs: &mut Defs) -> Result<Value> {
+    let (_, type_def) = type_id.resolve_ref()?;
+    let Some(name) = type_def.name().map(|n| n.to_string()) else {
+        return def_schema(&type_def, defs);
+    };
+    if !defs.contains_key(&name) {
+        // placeholder for recursive types
+        defs.insert(name.clone(), Value::Null);
+        let schema = def_schema(&type_def, defs)?;
+        defs.insert(name.clone(), schema);
+    }
+    Ok(json!({ "$ref": format!("#/$defs/{name}") }))
+}
+
+fn def_schema(type_def: &TypeDef, defs: &mut Defs) -> Result<Value> {
+    let mut schema = match type_def {
+        TypeDef::Boolean(_) => json!({ "type": "boolean" }),
+        TypeDef::Any(_) => json!({}),
+        TypeDef::Integer(inner) => {
+            let data = &inner.data;
+            let mut schema = json!({ "type": "integer" });
+            set(&mut schema, "minimum", data.min);
+            set(&mut schema, "maximum", data.max);
+            set(&mut schema, "exclusiveMinimum", data.exclusive_minimum);
+            set(&mut schema, "exclusiveMaximum", data.exclusive_maximum);
+            set(&mut schema, "multipleOf", data.multiple_of);
+            set(&mut schema, "enum", data.enumeration.clone());
+            schema
+        }
+        TypeDef::Float(inner) => {
+            let data = &inner.data;
+            let mut schema = json!({ "type": "number" });
+            set(&mut schema, "minimum", data.min);
+            set(&mut schema, "maximum", data.max);
+            set(&mut schema, "exclusiveMinimum", data.exclusive_minimum);
+            set(&mut schema, "exclusiveMaximum", data.exclusive_maximum);
+            set(&mut schema, "multipleOf", data.multiple_of);
+            set(&mut schema, "enum", data.enumeration.clone());
+            schema
+        }
+        TypeDef::String(inner) => {
+            let data = &inner.data;
+            let mut schema = json!({ "type": "string" });
+            set(&mut schema, "minLength", data.min);
+            set(&mut schema, "maxLength", data.max);
+            set(&mut schema, "pattern", data.pattern.clone());
+            set(&mut schema, "format", data.format.clone());
+            set(&mut schema, "enum", parse_all(data.enumeration.as_deref())?);
+            schema
+        }
+        TypeDef::List(inner) => {
+            let data = &inner.data;
+            let mut schema = json!({
+                "type": "array",
+                "items": type_schema(data.of.into(), defs)?,
+            });
+            set(&mut schema, "minItems", data.min);
+            set(&mut schema, "maxItems", data.max);
+            set(&mut schema, "uniqueItems", data.unique_items);
+            schema
+        }
+        TypeDef::Optional(inner) => {
+            let mut schema = json!({
+                "anyOf": [type_schema(inner.data.of.into(), defs)?, { "type": "null" }],
+            });
+            set(
+                &mut schema,
+                "default",
+                inner.data.default_item.as_deref().map(parse).transpose()?,
+            );
+            schema
+        }
+        TypeDef::Struct(inner) => {
+            let mut properties = serde_json::Map::new();
+            let mut required = vec![];
+            for (name, prop_id) in inner.iter_props() {
+                match prop_id.resolve_ref()?.1 {
+                    TypeDef::Optional(optional) => {
+                        properties.insert(
+                            name.to_string(),
+                            type_schema(optional.data.of.into(), defs)?,
+                        );
+                    }
+                    _ => {
+                        properties.insert(name.to_string(), type_schema(prop_id, defs)?);
+                        required.push(name.to_string());
+                    }
+                }
+            }
+            let data = &inner.data;
+            let mut schema = json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": data.additional_props,
+            });
+            set(&mut schema, "minProperties", data.min);
+            set(&mut schema, "maxProperties", data.max);
+            set(&mut schema, "enum", parse_all(data.enumeration.as_deref())?);
+            schema
+        }
+        TypeDef::Union(inner) => json!({
+            "anyOf": inner
+                .data
+                .variants
+                .iter()
+                .map(|v| type_schema(v.into(), defs))
+                .collect::<Result<Vec<_>>>()?,
+        }),
+        TypeDef::Either(inner) => json!({
+            "oneOf": inner
+                .data
+                .variants
+                .iter()
+                .map(|v| type_schema(v.into(), defs))
+                .collect::<Result<Vec<_>>>()?,
+        }),
+        TypeDef::File(_) | TypeDef::Func(_) => {
+            return Err(errors::invalid_type(
+                "a JSON data type",
+                type_def.variant_name(),
+            ));
+        }
+    };
+
+    if let Some(name) = type_def.name() {
+        schema["title"] = json!(name);
+    }
+    // annotations set through the runtime config
+    for (key, value) in type_def.base().runtime_config.iter().flatten() {
+        let keyword = match key.as_str() {
+            DESCRIPTION_CONFIG_KEY => "description",
+            "schema_id" => "$id",
+            "deprecated" => "deprecated",
+            "examples" => "examples",
+            _ => continue,
+        };
+        schema[keyword] = parse(value)?;
+    }
+    Ok(schema)
+}
+
+fn set(schema: &mut Value, keyword: &str, value: Option<impl serde::Serialize>) {
+    if let Some(value) = value {
+        schema[keyword] = json!(value);
+    }
+}
+
+fn parse(value: &str) -> Result<Value> {
+    serde_json::from_str(value).map_err(|e| errors::TgError::from(e.to_string()))
+}
+
+/// Parses JSON-serialized values, as in enumerations
+fn parse_all(values: Option<&[String]>) -> Result<Option<Vec<Value>>> {
+    values
+        .map(|values| values.iter().map(|v| parse(v)).collect())
+        .transpose()
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MPL-2.0
 
 pub mod hash;
+pub mod json_schema;
 pub mod parameter_transform;
 pub mod params;
 pub mod policies;
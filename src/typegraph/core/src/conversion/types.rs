@@ -7,10 +7,14 @@ use enum_dispatch::enum_dispatch;
 use indexmap::IndexMap;
 use std::rc::Rc;
 
-use crate::errors::Result;
+use crate::errors::{self, Result};
 use crate::typegraph::TypegraphContext;
 use crate::types::TypeId;
 
+/// Config key reserved for the type description: the wit type base has no
+/// description field
+pub const DESCRIPTION_CONFIG_KEY: &str = "__description";
+
 #[enum_dispatch]
 pub trait TypeConversion {
     /// takes already converted runtime id
@@ -76,15 +80,28 @@ impl<'a, 'b> BaseBuilderInit<'a, 'b> {
 
 impl<'a> BaseBuilder<'a> {
     pub fn build(self) -> Result<TypeNodeBase> {
-        let config = self.runtime_config.map(|c| {
-            c.iter()
-                .map(|(k, v)| (k.to_string(), serde_json::from_str(v).unwrap()))
-                .collect::<IndexMap<_, _>>()
-        });
+        let mut config = self
+            .runtime_config
+            .map(|c| {
+                c.iter()
+                    .map(|(k, v)| (k.to_string(), serde_json::from_str(v).unwrap()))
+                    .collect::<IndexMap<String, serde_json::Value>>()
+            })
+            .unwrap_or_default();
+        let description = match config.shift_remove(DESCRIPTION_CONFIG_KEY) {
+            Some(serde_json::Value::String(description)) => Some(description),
+            Some(value) => {
+                return Err(errors::invalid_config_value(
+                    DESCRIPTION_CONFIG_KEY,
+                    &format!("expected a string, got {value}"),
+                ))
+            }
+            None => None,
+        };
 
         Ok(TypeNodeBase {
-            config: config.unwrap_or_default(),
-            description: None,
+            config,
+            description,
             enumeration: self.enumeration,
             injection: self.injection,
             policies: self.policies,
@@ -52,10 +52,58 @@ pub fn invalid_max_value() -> TgError {
     "min must be less than or equal to max".into()
 }
 
+pub fn non_finite_value(name: &str) -> TgError {
+    format!("{name} must be a finite number").into()
+}
+
+pub fn invalid_multiple_of() -> TgError {
+    "multiple_of must be strictly positive".into()
+}
+
+pub fn empty_enumeration() -> TgError {
+    "enumeration must have at least one value".into()
+}
+
+pub fn unsupported_literal(value: &str) -> TgError {
+    format!("unsupported literal {value}: expected a number or a string").into()
+}
+
+pub fn duplicate_enum_value(value: &str) -> TgError {
+    format!("duplicate value {value} in enumeration").into()
+}
+
+pub fn inconsistent_enum_values(values: &[String]) -> TgError {
+    format!(
+        "enumeration values {} do not satisfy the constraints of the type",
+        values.join(", ")
+    )
+    .into()
+}
+
+pub fn missing_list_item() -> TgError {
+    "list item type is not set".into()
+}
+
+pub fn empty_variants(kind: &str) -> TgError {
+    format!("{kind} must have at least one variant").into()
+}
+
 pub fn duplicate_key(name: &str) -> TgError {
     format!("duplicate key '{name}' in properties").into()
 }
 
+pub fn missing_id_prop(model: &str) -> TgError {
+    format!("no prop marked as id in {model}").into()
+}
+
+pub fn unknown_key(name: &str) -> TgError {
+    format!("unknown key '{name}' in properties").into()
+}
+
+pub fn invalid_config_value(key: &str, reason: &str) -> TgError {
+    format!("invalid value for config '{key}': {reason}").into()
+}
+
 pub fn invalid_input_type(got: &str) -> TgError {
     format!("expected a Struct as input type but got {got}").into()
 }
@@ -64,6 +112,10 @@ pub fn invalid_type(expected: &str, got: &str) -> TgError {
     format!("expected {expected} but got {got}").into()
 }
 
+pub fn missing_scopes_provider(func: &str) -> TgError {
+    format!("{func} requires OAuth scopes but no OAuth2 auth provider is declared").into()
+}
+
 pub fn nested_typegraph_context(active: &str) -> TgError {
     format!("cannot init typegraph: typegraph '{active}' is still active").into()
 }
@@ -85,10 +137,18 @@ pub fn duplicate_export_name(name: &str) -> TgError {
     format!("duplicate export name '{name}'").into()
 }
 
+pub fn anonymous_type_ref(type_repr: &str) -> TgError {
+    format!("cannot reference anonymous type {type_repr}: name it first").into()
+}
+
 pub fn unregistered_type_name(name: &str) -> TgError {
     format!("type name '{name}' has not been registered").into()
 }
 
+pub fn unexpected_ref_variant(name: &str, expected: &str, got: &str) -> TgError {
+    format!("type '{name}' referenced as {expected} is a {got}").into()
+}
+
 pub fn object_not_found(kind: &str, id: u32) -> TgError {
     format!("{kind} #{id} not found").into()
 }
@@ -123,10 +183,30 @@ pub fn unknown_predefined_function(name: &str, runtime: &str) -> TgError {
     format!("unknown predefined function {name} for runtime {runtime}").into()
 }
 
+pub fn unregistered_policy(id: u32) -> TgError {
+    format!("policy #{id} is not registered: register the policy before attaching it").into()
+}
+
+pub fn missing_discriminator(field: &str, variant: &str) -> TgError {
+    format!("discriminator '{field}' is not a prop of the union variant {variant}").into()
+}
+
+pub fn undeclared_secret(name: &str) -> TgError {
+    format!("secret '{name}' is referenced but not declared").into()
+}
+
+pub fn undeclared_context_field(field: &str) -> TgError {
+    format!("context field '{field}' is not declared in the context schema").into()
+}
+
 pub fn duplicate_policy_name(name: &str) -> TgError {
     format!("duplicate policy name '{name}'").into()
 }
 
+pub fn duplicate_effect_policy(effect: &str) -> TgError {
+    format!("policy for effect '{effect}' is already set").into()
+}
+
 pub fn nested_type_ref(name: &str, nested: &str) -> TgError {
     format!("nested type reference is not allowed: -> '{name}' -> '{nested}'").into()
 }
@@ -100,6 +100,8 @@ impl Store {
 
 const PREDEFINED_DENO_FUNCTIONS: &[&str] = &["identity", "true"];
 
+// The store, as the typegraph context, is thread local: typegraphs built on
+// different threads never share type ids or names.
 thread_local! {
     pub static STORE: RefCell<Store> = RefCell::new(Store::new());
     pub static SDK_VERSION: String = "0.4.10-rc1".to_owned();
@@ -410,6 +412,17 @@ impl Store {
         })
     }
 
+    /// Policies are scoped to the active typegraph: the lookup does not see
+    /// the policies dropped by the restore of a previous typegraph context.
+    pub fn get_policy_by_name(name: &str) -> Option<PolicyId> {
+        with_store(|s| {
+            s.policies
+                .iter()
+                .position(|p| p.name == name)
+                .map(|idx| idx as PolicyId)
+        })
+    }
+
     pub fn get_public_policy_id() -> PolicyId {
         with_store(|s| s.public_policy_id)
     }
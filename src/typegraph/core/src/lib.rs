@@ -24,13 +24,17 @@ use global_store::{NameRegistration, Store};
 use indoc::formatdoc;
 use params::apply;
 use regex::Regex;
-use runtimes::{DenoMaterializer, Materializer};
+use runtimes::{DenoMaterializer, Materializer, MaterializerData};
 use types::{
-    Boolean, Either, File, Float, Func, Integer, List, Optional, StringT, Struct, TypeBoolean,
-    TypeDef, TypeDefExt, TypeId, Union,
+    Any, Boolean, Either, File, Float, Func, Integer, List, Optional, StringT, Struct, TypeAny,
+    TypeBoolean, TypeDef, TypeDefExt, TypeId, Union,
 };
 
 use utils::clear_name;
+use validation::types::{
+    check_float, check_integer, check_string_with_pattern, ensure_consistent_enumeration,
+    ensure_registered_policies, ensure_unique_enum_values, string_pattern,
+};
 use wit::core::{
     Artifact, ContextCheck, Policy, PolicyId, PolicySpec, SerializeParams, TransformData, TypeBase,
     TypeEither, TypeFile, TypeFloat, TypeFunc, TypeId as CoreTypeId, TypeInteger, TypeList,
@@ -63,8 +67,11 @@ impl wit::core::Guest for Lib {
     }
 
     fn integerb(data: TypeInteger, base: TypeBase) -> Result<CoreTypeId> {
+        if data.multiple_of.map_or(false, |m| m <= 0) {
+            return Err(errors::invalid_multiple_of());
+        }
         if let (Some(min), Some(max)) = (data.min, data.max) {
-            if min >= max {
+            if min > max {
                 return Err(errors::invalid_max_value());
             }
         }
@@ -73,6 +80,16 @@ impl wit::core::Guest for Lib {
                 return Err(errors::invalid_max_value());
             }
         }
+        if let Some(enumeration) = &data.enumeration {
+            ensure_unique_enum_values(enumeration)?;
+            let constraints = TypeInteger {
+                enumeration: None,
+                ..data.clone()
+            };
+            ensure_consistent_enumeration(enumeration.iter().map(|v| serde_json::json!(v)), |v| {
+                check_integer(v, &constraints, "enumeration")
+            })?;
+        }
         Ok(Store::register_type_def(
             |id| {
                 TypeDef::Integer(
@@ -91,8 +108,23 @@ impl wit::core::Guest for Lib {
     }
 
     fn floatb(data: TypeFloat, base: TypeBase) -> Result<CoreTypeId> {
+        let bounds = [
+            ("min", data.min),
+            ("max", data.max),
+            ("exclusive_minimum", data.exclusive_minimum),
+            ("exclusive_maximum", data.exclusive_maximum),
+            ("multiple_of", data.multiple_of),
+        ];
+        for (name, value) in bounds {
+            if value.map_or(false, |v| !v.is_finite()) {
+                return Err(errors::non_finite_value(name));
+            }
+        }
+        if data.multiple_of.map_or(false, |m| m <= 0.0) {
+            return Err(errors::invalid_multiple_of());
+        }
         if let (Some(min), Some(max)) = (data.min, data.max) {
-            if min >= max {
+            if min > max {
                 return Err(errors::invalid_max_value());
             }
         }
@@ -101,6 +133,16 @@ impl wit::core::Guest for Lib {
                 return Err(errors::invalid_max_value());
             }
         }
+        if let Some(enumeration) = &data.enumeration {
+            ensure_unique_enum_values(enumeration)?;
+            let constraints = TypeFloat {
+                enumeration: None,
+                ..data.clone()
+            };
+            ensure_consistent_enumeration(enumeration.iter().map(|v| serde_json::json!(v)), |v| {
+                check_float(v, &constraints, "enumeration")
+            })?;
+        }
         Ok(Store::register_type_def(
             |id| {
                 TypeDef::Float(
@@ -136,12 +178,45 @@ impl wit::core::Guest for Lib {
         .into())
     }
 
+    fn anyb(base: TypeBase) -> Result<CoreTypeId> {
+        Ok(Store::register_type_def(
+            |id| {
+                TypeDef::Any(
+                    Any {
+                        id,
+                        base,
+                        extended_base: Default::default(),
+                        data: TypeAny,
+                    }
+                    .into(),
+                )
+            },
+            NameRegistration(true),
+        )?
+        .into())
+    }
+
     fn stringb(data: TypeString, base: TypeBase) -> Result<CoreTypeId> {
         if let (Some(min), Some(max)) = (data.min, data.max) {
-            if min >= max {
+            if min > max {
                 return Err(errors::invalid_max_value());
             }
         }
+        if let Some(enumeration) = &data.enumeration {
+            ensure_unique_enum_values(enumeration)?;
+            let constraints = TypeString {
+                enumeration: None,
+                ..data.clone()
+            };
+            let pattern = string_pattern(&constraints)?;
+            ensure_consistent_enumeration(
+                enumeration
+                    .iter()
+                    .map(|v| serde_json::from_str(v).map_err(|e| TgError::from(e.to_string())))
+                    .collect::<Result<Vec<_>>>()?,
+                |v| check_string_with_pattern(v, &constraints, pattern.as_ref(), "enumeration"),
+            )?;
+        }
         Ok(Store::register_type_def(
             |id| {
                 TypeDef::String(
@@ -161,7 +236,7 @@ impl wit::core::Guest for Lib {
 
     fn fileb(data: TypeFile, base: TypeBase) -> Result<CoreTypeId> {
         if let (Some(min), Some(max)) = (data.min, data.max) {
-            if min >= max {
+            if min > max {
                 return Err(errors::invalid_max_value());
             }
         }
@@ -188,6 +263,9 @@ impl wit::core::Guest for Lib {
     }
 
     fn listb(data: TypeList, base: TypeBase) -> Result<CoreTypeId> {
+        if data.of == u32::MAX {
+            return Err(errors::missing_list_item());
+        }
         if let (Some(min), Some(max)) = (data.min, data.max) {
             if min > max {
                 return Err(errors::invalid_max_value());
@@ -251,6 +329,9 @@ impl wit::core::Guest for Lib {
     }
 
     fn unionb(data: TypeUnion, base: TypeBase) -> Result<CoreTypeId> {
+        if data.variants.is_empty() {
+            return Err(errors::empty_variants("union"));
+        }
         Ok(Store::register_type_def(
             |id| {
                 TypeDef::Union(
@@ -269,6 +350,9 @@ impl wit::core::Guest for Lib {
     }
 
     fn eitherb(data: TypeEither, base: TypeBase) -> Result<CoreTypeId> {
+        if data.variants.is_empty() {
+            return Err(errors::empty_variants("either"));
+        }
         Ok(Store::register_type_def(
             |id| {
                 TypeDef::Either(
@@ -345,6 +429,7 @@ impl wit::core::Guest for Lib {
         if !matches!(TypeDef::try_from(wrapper_type)?, TypeDef::Struct(_)) {
             return Err(errors::invalid_input_type(&wrapper_type.repr()?));
         }
+        Store::get_materializer(data.mat)?.validate_path_params(&data)?;
 
         let base = TypeBase::default();
         Ok(Store::register_type_def(
@@ -412,6 +497,7 @@ impl wit::core::Guest for Lib {
         if !x_base.policies.is_empty() {
             return Err(errors::TgError::from("policy already exists for this type"));
         }
+        ensure_registered_policies(&policy_chain)?;
         x_base.policies = policy_chain;
         Ok(Store::register_type_def(
             move |id| type_def.with_x_base(id, x_base.clone()),
@@ -433,6 +519,11 @@ impl wit::core::Guest for Lib {
     }
 
     fn get_internal_policy() -> Result<(PolicyId, String)> {
+        let name = "__internal".to_string();
+        if let Some(policy_id) = Store::get_policy_by_name(&name) {
+            return Ok((policy_id, name));
+        }
+
         let deno_mat = DenoMaterializer::Predefined(wit::runtimes::MaterializerDenoPredefined {
             name: "internal_policy".to_string(),
         });
@@ -440,7 +531,7 @@ impl wit::core::Guest for Lib {
         let policy_id = Store::register_policy(
             Policy {
                 materializer: Store::register_materializer(mat),
-                name: "__internal".to_string(),
+                name,
             }
             .into(),
         )?;
@@ -487,6 +578,22 @@ impl wit::core::Guest for Lib {
             }}
         "# };
 
+        // the sanitized name is not unique to the check: only reuse the policy
+        // when it runs the same code
+        if let Some(policy_id) = Store::get_policy_by_name(&name) {
+            let mat = Store::get_materializer(Store::get_policy(policy_id)?.materializer)?;
+            let same_check = match &mat.data {
+                MaterializerData::Deno(deno) => {
+                    matches!(deno.as_ref(), DenoMaterializer::Inline(f) if f.code == code)
+                }
+                _ => false,
+            };
+            return match same_check {
+                true => Ok((policy_id, name)),
+                false => Err(errors::duplicate_policy_name(&name)),
+            };
+        }
+
         let mat_id = Lib::register_deno_func(
             MaterializerDenoFunc {
                 code,
@@ -534,6 +641,10 @@ impl wit::core::Guest for Lib {
     fn set_seed(seed: Option<u32>) -> Result<()> {
         typegraph::set_seed(seed)
     }
+
+    fn declare_context(schema: CoreTypeId) -> Result<()> {
+        typegraph::declare_context(schema.into())
+    }
 }
 
 #[macro_export]
@@ -545,14 +656,33 @@ macro_rules! log {
 
 #[cfg(test)]
 mod tests {
+    use crate::conversion::types::DESCRIPTION_CONFIG_KEY;
     use crate::errors::{self, Result};
     use crate::global_store::Store;
-    use crate::t::{self, TypeBuilder};
+    use crate::t::{self, ConcreteTypeBuilder, TypeBuilder};
     use crate::test_utils::setup;
-    use crate::wit::core::{Cors, Guest, MigrationAction, PrismaMigrationConfig, SerializeParams};
+    use crate::types::{TypeDef, TypeDefExt, TypeId};
+    use crate::wit::core::{
+        ContextCheck, Cors, Guest, MigrationAction, PolicySpec, PrismaMigrationConfig,
+        SerializeParams,
+    };
     use crate::wit::runtimes::{Effect, Guest as GuestRuntimes, MaterializerDenoFunc};
+    use crate::wit::utils::Guest as GuestUtils;
     use crate::Lib;
-    use crate::TypegraphInitParams;
+    use crate::{TypeFloat, TypeInteger, TypeString, TypegraphInitParams};
+
+    impl Default for Cors {
+        fn default() -> Self {
+            Self {
+                allow_origin: vec![],
+                allow_headers: vec![],
+                expose_headers: vec![],
+                allow_methods: vec![],
+                allow_credentials: false,
+                max_age_sec: None,
+            }
+        }
+    }
 
     impl Default for TypegraphInitParams {
         fn default() -> Self {
@@ -561,14 +691,7 @@ mod tests {
                 dynamic: None,
                 path: ".".to_string(),
                 prefix: None,
-                cors: Cors {
-                    allow_origin: vec![],
-                    allow_headers: vec![],
-                    expose_headers: vec![],
-                    allow_methods: vec![],
-                    allow_credentials: false,
-                    max_age_sec: None,
-                },
+                cors: Cors::default(),
                 rate: None,
             }
         }
@@ -601,6 +724,7 @@ mod tests {
         assert_eq!(res, Err(errors::invalid_max_value()));
         let res = t::integer().x_min(12).x_max(12).build();
         assert_eq!(res, Err(errors::invalid_max_value()));
+        assert!(t::integer().min(12).max(12).build().is_ok());
     }
 
     #[test]
@@ -611,6 +735,176 @@ mod tests {
         assert_eq!(res, Err(errors::invalid_max_value()));
     }
 
+    #[test]
+    fn test_string_invalid_max() -> Result<()> {
+        let res = Lib::stringb(
+            TypeString {
+                min: Some(5),
+                max: Some(2),
+                ..Default::default()
+            },
+            Default::default(),
+        );
+        assert_eq!(res, Err(errors::invalid_max_value()));
+        Lib::stringb(
+            TypeString {
+                min: Some(5),
+                max: Some(5),
+                ..Default::default()
+            },
+            Default::default(),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_finite_bounds() -> Result<()> {
+        let res = t::float().min(f64::NAN).build();
+        assert_eq!(res, Err(errors::non_finite_value("min")));
+        let res = t::float().max(f64::INFINITY).build();
+        assert_eq!(res, Err(errors::non_finite_value("max")));
+        let _bounded = t::float().min(-1.5).max(1.5).build()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_multiple_of() -> Result<()> {
+        let res = t::integer().multiple_of(0).build();
+        assert_eq!(res, Err(errors::invalid_multiple_of()));
+        let res = t::float().multiple_of(-0.5).build();
+        assert_eq!(res, Err(errors::invalid_multiple_of()));
+        let _even = t::integer().multiple_of(2).build()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_inconsistent_enumeration() -> Result<()> {
+        let res = t::enum_of(t::integer().min(10), [1, 5, 20]);
+        assert_eq!(
+            res,
+            Err(errors::inconsistent_enum_values(&[
+                "1".to_string(),
+                "5".to_string()
+            ]))
+        );
+        let res = t::string()
+            .max(5)
+            .enum_(vec!["short".to_string(), "too long".to_string()])
+            .build();
+        assert_eq!(
+            res,
+            Err(errors::inconsistent_enum_values(&[
+                "\"too long\"".to_string()
+            ]))
+        );
+        let _consistent = t::enum_of(t::integer().min(1), [1, 5, 20])?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_of() -> Result<()> {
+        let tpe = t::ListBuilder::default().min(1).of(t::integer())?.build()?;
+        let Some(TypeDef::List(inner)) = tpe.as_type_def()? else {
+            panic!("expected a list type");
+        };
+        assert!(matches!(
+            TypeId(inner.data.of).as_type_def()?,
+            Some(TypeDef::Integer(_))
+        ));
+        assert_eq!(inner.data.min, Some(1));
+
+        let res = t::ListBuilder::default().min(1).build();
+        assert_eq!(res, Err(errors::missing_list_item()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_invalid_max() -> Result<()> {
+        let item = t::string().build()?;
+        let res = t::list(item).min(5).max(1).build();
+        assert_eq!(res, Err(errors::invalid_max_value()));
+        let _exact = t::list(item).min(2).max(2).build()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_variants() {
+        let res = t::union(vec![]).build();
+        assert_eq!(res, Err(errors::empty_variants("union")));
+        let res = t::either(vec![]).build();
+        assert_eq!(res, Err(errors::empty_variants("either")));
+    }
+
+    #[test]
+    fn test_rename_type() -> Result<()> {
+        let original = t::integer().min(1).build()?;
+        let renamed: TypeId = Lib::rename_type(original.into(), "Count".to_string())?.into();
+        assert_ne!(renamed, original);
+        assert_eq!(renamed.name()?, Some("Count".to_string()));
+        assert_eq!(original.name()?, None);
+        assert_eq!(Store::get_type_by_name("Count"), Some(renamed));
+        let Some(TypeDef::Integer(inner)) = renamed.as_type_def()? else {
+            panic!("expected an integer type");
+        };
+        assert_eq!(inner.data.min, Some(1));
+
+        let reference = t::ref_("Count").build()?;
+        assert_eq!(
+            Lib::rename_type(reference.into(), "Other".to_string()),
+            Err(errors::TgError::from("cannot rename ref type"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_structural_equality() -> Result<()> {
+        let a = t::integer().min(1).build()?;
+        let b = t::integer().min(1).build()?;
+        assert_ne!(a, b);
+        assert!(a.structurally_eq(&b)?);
+        assert!(!a.structurally_eq(&t::integer().min(2).build()?)?);
+        assert!(!a.structurally_eq(&t::float().min(1.0).build()?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_enum_value() -> Result<()> {
+        let res = Lib::integerb(
+            TypeInteger {
+                enumeration: Some(vec![1, 1, 2]),
+                ..Default::default()
+            },
+            Default::default(),
+        );
+        assert_eq!(res, Err(errors::duplicate_enum_value("1")));
+
+        let res = Lib::floatb(
+            TypeFloat {
+                enumeration: Some(vec![1.5, 2.5, 2.5]),
+                ..Default::default()
+            },
+            Default::default(),
+        );
+        assert_eq!(res, Err(errors::duplicate_enum_value("2.5")));
+
+        let res = t::string()
+            .enum_(vec!["a".to_string(), "b".to_string(), "a".to_string()])
+            .build();
+        assert_eq!(res, Err(errors::duplicate_enum_value("\"a\"")));
+
+        t::string()
+            .enum_(vec!["a".to_string(), "b".to_string()])
+            .build()?;
+        Lib::integerb(
+            TypeInteger {
+                enumeration: Some(vec![1, 2]),
+                ..Default::default()
+            },
+            Default::default(),
+        )?;
+        Ok(())
+    }
+
     #[test]
     fn test_struct_duplicate_key() -> Result<()> {
         let res = t::struct_()
@@ -622,6 +916,215 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_context_policy() -> Result<()> {
+        let (policy_id, name) = Lib::register_context_policy(
+            "role".to_string(),
+            ContextCheck::Value("admin".to_string()),
+        )?;
+        assert_eq!(name, "__ctx_role_admin");
+
+        let tpe = t::integer()
+            .with_policy(vec![PolicySpec::Simple(policy_id)])
+            .build()?;
+        let policies = tpe.as_type_def()?.unwrap().x_base().policies.clone();
+        assert!(matches!(policies.as_slice(), [PolicySpec::Simple(id)] if *id == policy_id));
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_chain() -> Result<()> {
+        let check = |value: &str| {
+            Lib::register_context_policy("role".to_string(), ContextCheck::Value(value.to_string()))
+                .map(|(id, _)| id)
+        };
+        let (admin, user, guest) = (check("admin")?, check("user")?, check("guest")?);
+        let single = PolicySpec::Simple(admin);
+        let chain = [
+            single.clone(),
+            PolicySpec::Simple(user),
+            PolicySpec::Simple(guest),
+        ];
+        let policies = |tpe: TypeId| -> Result<_> {
+            Ok(tpe.as_type_def()?.unwrap().x_base().policies.clone())
+        };
+
+        let tpe = t::integer().with_policy(&single).build()?;
+        assert_eq!(policies(tpe)?.len(), 1);
+        let tpe = t::integer().with_policy(admin).build()?;
+        assert_eq!(policies(tpe)?.len(), 1);
+
+        let tpe = t::integer().with_policy(&chain[..]).build()?;
+        let ids = |chain: Vec<PolicySpec>| {
+            chain
+                .into_iter()
+                .map(|p| match p {
+                    PolicySpec::Simple(id) => id,
+                    PolicySpec::PerEffect(_) => panic!("expected a simple policy"),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(policies(tpe)?), vec![admin, user, guest]);
+        let tpe = t::integer().with_policies(chain).build()?;
+        assert_eq!(ids(policies(tpe)?), vec![admin, user, guest]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_policy() -> Result<()> {
+        let (public_id, _) = Lib::get_public_policy()?;
+        let tpe = t::integer()
+            .with_policy(vec![PolicySpec::Simple(public_id)])
+            .build()?;
+        assert!(tpe.has_policy()?);
+        assert!(!t::integer().build()?.has_policy()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unregistered_policy() -> Result<()> {
+        let (public_id, _) = Lib::get_public_policy()?;
+        let bogus_id = 9999;
+
+        let res = Lib::with_policy(
+            t::integer().build()?.into(),
+            vec![PolicySpec::Simple(bogus_id)],
+        );
+        assert_eq!(res, Err(errors::unregistered_policy(bogus_id)));
+
+        let res = t::integer()
+            .with_policy_per_effect([(Effect::Read, public_id), (Effect::Create(true), bogus_id)])?
+            .build();
+        assert_eq!(res, Err(errors::unregistered_policy(bogus_id)));
+
+        Lib::with_policy(
+            t::integer().build()?.into(),
+            vec![PolicySpec::Simple(public_id)],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_per_effect() -> Result<()> {
+        let (public_id, _) = Lib::get_public_policy()?;
+        let (internal_id, _) = Lib::get_internal_policy()?;
+
+        let tpe = t::integer()
+            .with_policy_per_effect([
+                (Effect::Read, public_id),
+                (Effect::Create(true), internal_id),
+            ])?
+            .build()?;
+        let policies = tpe.as_type_def()?.unwrap().x_base().policies.clone();
+        match policies.as_slice() {
+            [PolicySpec::PerEffect(p)] => {
+                assert_eq!(p.read, Some(public_id));
+                assert_eq!(p.create, Some(internal_id));
+                assert_eq!(p.update, None);
+                assert_eq!(p.delete, None);
+            }
+            _ => panic!("expected a per-effect policy chain"),
+        }
+
+        let res = t::integer()
+            .with_policy_per_effect([
+                (Effect::Update(true), public_id),
+                (Effect::Update(false), internal_id),
+            ])
+            .map(|_| ());
+        assert_eq!(res, Err(errors::duplicate_effect_policy("update")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_policy_reuse() -> Result<()> {
+        Store::reset();
+        let register = || {
+            Lib::register_context_policy(
+                "role".to_string(),
+                ContextCheck::Value("admin".to_string()),
+            )
+        };
+
+        setup(Some("first"))?;
+        let (policy_id, name) = register()?;
+        assert_eq!(register()?, (policy_id, name.clone()));
+        let (internal_id, _) = Lib::get_internal_policy()?;
+        assert_eq!(Lib::get_internal_policy()?.0, internal_id);
+        Lib::serialize_typegraph(Default::default())?;
+        assert_eq!(Store::get_policy_by_name(&name), None);
+
+        setup(Some("second"))?;
+        let (policy_id, second_name) = register()?;
+        assert_eq!(second_name, name);
+        assert_eq!(Store::get_policy(policy_id)?.name, name);
+        Lib::serialize_typegraph(Default::default())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_builtin_policies() -> Result<()> {
+        let (public_id, public_name) = Lib::get_public_policy()?;
+        assert_eq!(public_id, Store::get_public_policy_id());
+        assert_eq!(public_name, "__public");
+
+        let (internal_id, internal_name) = Lib::get_internal_policy()?;
+        assert_eq!(internal_name, "__internal");
+
+        for policy_id in [public_id, internal_id] {
+            let tpe = Lib::with_policy(
+                t::integer().build()?.into(),
+                vec![PolicySpec::Simple(policy_id)],
+            )?;
+            let policies = TypeId(tpe)
+                .as_type_def()?
+                .unwrap()
+                .x_base()
+                .policies
+                .clone();
+            assert!(matches!(policies.as_slice(), [PolicySpec::Simple(id)] if *id == policy_id));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_func_effect() -> Result<()> {
+        let create = Lib::register_deno_func(
+            MaterializerDenoFunc::with_code("() => 12"),
+            Effect::Create(true),
+        )?;
+        let func = t::FuncBuilder::new(t::struct_().build()?, t::integer().build()?, create);
+        assert!(matches!(func.effect()?, Effect::Create(true)));
+
+        let read = Lib::register_deno_func(
+            MaterializerDenoFunc::with_code("() => 12"),
+            Effect::default(),
+        )?;
+        let func = t::FuncBuilder::new(t::struct_().build()?, t::integer().build()?, read);
+        assert!(matches!(func.effect()?, Effect::Read));
+        Ok(())
+    }
+
+    #[test]
+    fn test_colliding_policy_names() -> Result<()> {
+        setup(None)?;
+        let register = |key: &str, value: &str| {
+            Lib::register_context_policy(key.to_string(), ContextCheck::Value(value.to_string()))
+        };
+
+        let (_, name) = register("role", "a.b")?;
+        assert_eq!(
+            register("role", "a-b"),
+            Err(errors::duplicate_policy_name(&name))
+        );
+        let (_, name) = register("user.id", "admin")?;
+        assert_eq!(
+            register("user_id", "admin"),
+            Err(errors::duplicate_policy_name(&name))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_invalid_input_type() -> Result<()> {
         let mat =
@@ -727,6 +1230,820 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_expose_duplicate_across_calls() -> Result<()> {
+        setup(None)?;
+        let read =
+            Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+        let create = Lib::register_deno_func(
+            MaterializerDenoFunc::with_code("() => 12"),
+            Effect::Create(true),
+        )?;
+        let func = |mat| t::func(t::struct_().build()?, t::integer().build()?, mat);
+
+        Lib::expose(vec![("create".to_string(), func(create)?.into())], None)?;
+        Lib::expose(vec![("read".to_string(), func(read)?.into())], None)?;
+        let res = Lib::expose(vec![("create".to_string(), func(read)?.into())], None);
+        assert_eq!(res, Err(errors::duplicate_export_name("create")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expose_default_policy() -> Result<()> {
+        setup(None)?;
+        let (public_id, _) = Lib::get_public_policy()?;
+        let (internal_id, _) = Lib::get_internal_policy()?;
+        let mat =
+            Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+
+        let one = t::func(t::struct_().build()?, t::integer().build()?, mat)?;
+        let two = t::func(t::struct_().build()?, t::integer().build()?, mat)?;
+        let two = Lib::with_policy(two.into(), vec![PolicySpec::Simple(internal_id)])?;
+        Lib::expose(
+            vec![("one".to_string(), one.into()), ("two".to_string(), two)],
+            Some(vec![PolicySpec::Simple(public_id)]),
+        )?;
+
+        let (typegraph, _) = Lib::serialize_typegraph(Default::default())?;
+        let tg: serde_json::Value = serde_json::from_str(&typegraph).unwrap();
+        let policy_name = |field: &str| {
+            let idx = tg["types"][0]["properties"][field].as_u64().unwrap() as usize;
+            let policy_idx = tg["types"][idx]["policies"][0].as_u64().unwrap() as usize;
+            tg["policies"][policy_idx]["name"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(policy_name("one"), "__public");
+        assert_eq!(policy_name("two"), "__internal");
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_serialization() -> Result<()> {
+        setup(None)?;
+        let mat =
+            Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+        let one = t::func(t::struct_().build()?, t::integer().build()?, mat)?;
+        Lib::expose(vec![("one".to_string(), one.into())], None)?;
+
+        let (typegraph, _) = Lib::serialize_typegraph(SerializeParams {
+            pretty: false,
+            ..Default::default()
+        })?;
+        assert!(!typegraph.contains('\n'));
+        let tg: serde_json::Value = serde_json::from_str(&typegraph).unwrap();
+        assert!(tg["types"][0]["properties"]["one"].is_u64());
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_typegraph() -> Result<()> {
+        let params = || TypegraphInitParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+
+        let res = crate::typegraph::build(params(), Default::default(), || {
+            let _leftover = t::integer().named("Leftover").build()?;
+            Err(errors::TgError::from("failed"))
+        });
+        assert_eq!(res, Err(errors::TgError::from("failed")));
+        assert!(Store::get_type_by_name("Leftover").is_none());
+
+        let (typegraph, _) = crate::typegraph::build(params(), Default::default(), || {
+            let mat =
+                Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+            let one = t::func(t::struct_().build()?, t::integer().build()?, mat)?;
+            Lib::expose(vec![("one".to_string(), one.into())], None)
+        })?;
+        let tg: serde_json::Value = serde_json::from_str(&typegraph).unwrap();
+        assert!(tg["types"][0]["properties"]["one"].is_u64());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursive_type() -> Result<()> {
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            // the ref is resolved by name, so the type can refer to itself
+            // before it is registered
+            let category = t::struct_()
+                .prop("name", t::string().build()?)
+                .propx("children", t::listx(t::ref_("Category"))?)?
+                .named("Category")
+                .build()?;
+            let mat =
+                Lib::register_deno_func(MaterializerDenoFunc::with_code("() => []"), Effect::Read)?;
+            let categories = t::func(t::struct_().build()?, category, mat)?;
+            Lib::expose(vec![("categories".to_string(), categories.into())], None)
+        })?;
+
+        let tg: serde_json::Value = serde_json::from_str(&typegraph).unwrap();
+        let types = &tg["types"];
+        let func = &types[tg["types"][0]["properties"]["categories"].as_u64().unwrap() as usize];
+        let category = func["output"].as_u64().unwrap();
+        assert_eq!(types[category as usize]["title"], "Category");
+        let children = &types[types[category as usize]["properties"]["children"]
+            .as_u64()
+            .unwrap() as usize];
+        assert_eq!(children["items"].as_u64(), Some(category));
+        Ok(())
+    }
+
+    #[test]
+    fn test_thread_isolation() {
+        let build = |name: &'static str| {
+            std::thread::spawn(move || -> Result<(TypeId, TypeId, String)> {
+                // registered in the global scope of the thread's store
+                let local = t::string().named("ThreadLocal").build()?;
+                let params = TypegraphInitParams {
+                    name: name.to_string(),
+                    ..Default::default()
+                };
+                let mut shared = None;
+                let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+                    let id = t::string().named("Shared").build()?;
+                    shared = Some(id);
+                    let mat = Lib::register_deno_func(
+                        MaterializerDenoFunc::with_code("() => ''"),
+                        Effect::Read,
+                    )?;
+                    let get = t::func(t::struct_().build()?, id, mat)?;
+                    Lib::expose(vec![("get".to_string(), get.into())], None)
+                })?;
+                Ok((local, shared.unwrap(), typegraph))
+            })
+        };
+
+        // same starting point as the fresh stores of the spawned threads
+        Store::reset();
+        let main_local = t::integer().named("ThreadLocal").build().unwrap();
+        let first = build("first");
+        let second = build("second");
+        let (first_local, first_id, first_tg) = first.join().unwrap().unwrap();
+        let (_, second_id, second_tg) = second.join().unwrap().unwrap();
+        // both threads start from the same clean store
+        assert_eq!(first_id, second_id);
+        let title = |tg: &str| {
+            let tg: serde_json::Value = serde_json::from_str(tg).unwrap();
+            tg["types"][0]["title"].as_str().unwrap().to_string()
+        };
+        assert_eq!(title(&first_tg), "first");
+        assert_eq!(title(&second_tg), "second");
+        // the spawned thread registered its string under the same id as the
+        // integer of this thread, which still resolves to the integer
+        assert_eq!(first_local, main_local);
+        assert_eq!(Store::get_type_by_name("ThreadLocal"), Some(main_local));
+        assert!(matches!(
+            first_local.as_type_def(),
+            Ok(Some(TypeDef::Integer(_)))
+        ));
+        assert_eq!(Store::get_type_by_name("Shared"), None);
+    }
+
+    #[test]
+    fn test_sequential_typegraphs() -> Result<()> {
+        let build = |name: &str| -> Result<TypeId> {
+            let params = TypegraphInitParams {
+                name: name.to_string(),
+                ..Default::default()
+            };
+            let mut first = None;
+            crate::typegraph::build(params, Default::default(), || {
+                let id = t::integer().named("Count").build()?;
+                first = Some(id);
+                let mat = Lib::register_deno_func(
+                    MaterializerDenoFunc::with_code("() => 0"),
+                    Effect::Read,
+                )?;
+                let count = t::func(t::struct_().build()?, id, mat)?;
+                Lib::expose(vec![("count".to_string(), count.into())], None)
+            })?;
+            Ok(first.unwrap())
+        };
+
+        // the store is restored when a typegraph is serialized: the second
+        // typegraph reuses the same ids and names
+        let first = build("first")?;
+        let second = build("second")?;
+        assert_eq!(first, second);
+        assert_eq!(Store::get_type_by_name("Count"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_openapi_export() -> Result<()> {
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            let user = t::struct_()
+                .prop("id", t::integer().build()?)
+                .propx("email", t::string().format("email"))?
+                .propx("nickname", t::optionalx(t::string())?)?
+                .named("User")
+                .build()?;
+            let input = t::struct_().prop("id", t::integer().build()?).build()?;
+            let mat = Lib::register_deno_func(
+                MaterializerDenoFunc::with_code("() => null"),
+                Effect::Read,
+            )?;
+            let find_user = t::func(input, user, mat)?;
+            Lib::expose(vec![("findUser".to_string(), find_user.into())], None)
+        })?;
+
+        let openapi = Lib::to_openapi(typegraph)?;
+        let openapi: serde_json::Value = serde_json::from_str(&openapi).unwrap();
+
+        let get = &openapi["paths"]["/findUser"]["get"];
+        assert_eq!(get["parameters"][0]["name"], "id");
+        assert_eq!(get["parameters"][0]["in"], "query");
+        assert_eq!(
+            get["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/User"
+        );
+        let user = &openapi["components"]["schemas"]["User"];
+        assert_eq!(user["properties"]["email"]["format"], "email");
+        assert_eq!(user["required"], serde_json::json!(["id", "email"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_graphql_sdl_export() -> Result<()> {
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            let role = t::string()
+                .enum_(vec!["admin".to_string(), "member".to_string()])
+                .named("Role")
+                .build()?;
+            let user = t::struct_()
+                .prop("id", t::integer().as_id(true).build()?)
+                .prop("role", role)
+                .propx("nickname", t::optionalx(t::string())?)?
+                .named("User")
+                .build()?;
+            let read = Lib::register_deno_func(
+                MaterializerDenoFunc::with_code("() => null"),
+                Effect::Read,
+            )?;
+            let create = Lib::register_deno_func(
+                MaterializerDenoFunc::with_code("() => null"),
+                Effect::Create(false),
+            )?;
+            let find_user = t::func(t::struct_().prop("role", role).build()?, user, read)?;
+            let create_user = t::func(user, user, create)?;
+            Lib::expose(
+                vec![
+                    ("findUser".to_string(), find_user.into()),
+                    ("createUser".to_string(), create_user.into()),
+                ],
+                None,
+            )
+        })?;
+
+        let sdl = Lib::to_graphql_sdl(typegraph)?;
+        assert!(sdl.contains("type Query {\n  findUser(role: Role!): User!\n}"));
+        assert!(sdl.contains(
+            "type Mutation {\n  createUser(id: ID!, role: Role!, nickname: String): User!\n}"
+        ));
+        assert!(sdl.contains("type User {\n  id: ID!\n  role: Role!\n  nickname: String\n}"));
+        assert!(sdl.contains("enum Role {\n  admin\n  member\n}"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_injected_input_hidden() -> Result<()> {
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            let create = Lib::register_deno_func(
+                MaterializerDenoFunc::with_code("() => null"),
+                Effect::Create(false),
+            )?;
+            let inp = t::struct_()
+                .prop("name", t::string().build()?)
+                .propx("tenantId", t::string().set_value("acme"))?
+                .build()?;
+            let create_user = t::func(inp, t::boolean().build()?, create)?;
+            Lib::expose(vec![("createUser".to_string(), create_user.into())], None)
+        })?;
+
+        let tg: common::typegraph::Typegraph = serde_json::from_str(&typegraph).unwrap();
+        let sdl = crate::utils::graphql_sdl::to_graphql_sdl(&tg)?;
+        assert!(sdl.contains("createUser(name: String!): Boolean!"));
+        let openapi = crate::utils::openapi::to_openapi(&tg)?;
+        assert!(openapi.contains("\"name\""));
+        assert!(!openapi.contains("tenantId"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_required_secrets() -> Result<()> {
+        use crate::utils::secrets::{ensure_declared_secrets, required_secrets};
+        use crate::wit::runtimes::{
+            BaseMaterializer, HttpMethod, HttpRuntimeData, MaterializerHttpRequest,
+        };
+        use common::typegraph::{Injection, InjectionData, SingleValue};
+
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            let runtime = Lib::register_http_runtime(HttpRuntimeData {
+                endpoint: "https://example.com/api".to_string(),
+                cert_secret: Some("API_CERT".to_string()),
+                basic_auth_secret: None,
+            })?;
+            let mat = Lib::http_request(
+                BaseMaterializer {
+                    runtime,
+                    effect: Effect::Read,
+                },
+                MaterializerHttpRequest {
+                    method: HttpMethod::Get,
+                    path: "/users".to_string(),
+                    content_type: None,
+                    header_prefix: None,
+                    query_fields: None,
+                    rename_fields: None,
+                    body_fields: None,
+                    auth_token_field: None,
+                },
+            )?;
+            let token = t::string()
+                .inject(Injection::Secret(InjectionData::SingleValue(SingleValue {
+                    value: "API_TOKEN".to_string(),
+                })))
+                .build()?;
+            let inp = t::struct_().prop("token", token).build()?;
+            let users = t::func(inp, t::list(t::string().build()?).build()?, mat)?;
+            Lib::expose(vec![("users".to_string(), users.into())], None)
+        })?;
+
+        let tg: common::typegraph::Typegraph = serde_json::from_str(&typegraph).unwrap();
+        assert_eq!(required_secrets(&tg), vec!["API_CERT", "API_TOKEN"]);
+        ensure_declared_secrets(&tg, &["API_CERT".to_string(), "API_TOKEN".to_string()])?;
+        assert_eq!(
+            ensure_declared_secrets(&tg, &["API_CERT".to_string()]),
+            Err(errors::undeclared_secret("API_TOKEN"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_schema() -> Result<()> {
+        use common::typegraph::{Injection, InjectionData, SingleValue};
+
+        let build = |field: &'static str| {
+            let params = TypegraphInitParams {
+                name: "test".to_string(),
+                ..Default::default()
+            };
+            crate::typegraph::build(params, Default::default(), || {
+                Lib::declare_context(
+                    t::struct_()
+                        .prop("userId", t::string().build()?)
+                        .prop("role", t::string().build()?)
+                        .build()?
+                        .into(),
+                )?;
+                let user_id = t::string()
+                    .inject(Injection::Context(InjectionData::SingleValue(
+                        SingleValue {
+                            value: field.to_string(),
+                        },
+                    )))
+                    .build()?;
+                let mat = Lib::register_deno_func(
+                    MaterializerDenoFunc::with_code("() => null"),
+                    Effect::Read,
+                )?;
+                let me = t::func(
+                    t::struct_().prop("id", user_id).build()?,
+                    t::string().build()?,
+                    mat,
+                )?;
+                Lib::expose(vec![("me".to_string(), me.into())], None)
+            })
+        };
+
+        build("userId")?;
+        let res = build("usrId").map(|_| ());
+        assert_eq!(res, Err(errors::undeclared_context_field("usrId")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cors() -> Result<()> {
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            cors: Cors {
+                allow_origin: vec![
+                    "https://app.example.com".to_string(),
+                    "https://admin.example.com".to_string(),
+                ],
+                allow_credentials: true,
+                max_age_sec: Some(600),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            let mat =
+                Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+            let one = t::func(t::struct_().build()?, t::integer().build()?, mat)?;
+            Lib::expose(vec![("one".to_string(), one.into())], None)
+        })?;
+
+        let tg: common::typegraph::Typegraph = serde_json::from_str(&typegraph).unwrap();
+        assert_eq!(
+            tg.meta.cors.allow_origin,
+            vec!["https://app.example.com", "https://admin.example.com"]
+        );
+        assert!(tg.meta.cors.allow_credentials);
+        assert_eq!(tg.meta.cors.max_age_sec, Some(600));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rate_limit() -> Result<()> {
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            rate: Some(crate::wit::core::Rate {
+                window_limit: 35,
+                window_sec: 15,
+                query_limit: 25,
+                context_identifier: Some("user".to_string()),
+                local_excess: 5,
+            }),
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            let mat =
+                Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+            let one = t::FuncBuilder::new(t::struct_().build()?, t::integer().build()?, mat)
+                .rate_weight(2)
+                .rate_calls(true)
+                .build()?;
+            Lib::expose(vec![("one".to_string(), one.into())], None)
+        })?;
+
+        let tg: common::typegraph::Typegraph = serde_json::from_str(&typegraph).unwrap();
+        let rate = tg.meta.rate.unwrap();
+        assert_eq!((rate.window_limit, rate.window_sec), (35, 15));
+        assert_eq!((rate.query_limit, rate.local_excess), (25, 5));
+        assert_eq!(rate.context_identifier.as_deref(), Some("user"));
+        let func = tg.types.iter().find_map(|node| match node {
+            common::typegraph::TypeNode::Function { data, .. } => Some(data),
+            _ => None,
+        });
+        let func = func.unwrap();
+        assert_eq!((func.rate_weight, func.rate_calls), (Some(2), true));
+        Ok(())
+    }
+
+    #[test]
+    fn test_double_build_dedup() -> Result<()> {
+        let params = TypegraphInitParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+        let (typegraph, _) = crate::typegraph::build(params, Default::default(), || {
+            let mut builder = t::struct_();
+            builder.prop("id", t::integer().build()?);
+            let (first, second) = (builder.build()?, builder.build()?);
+            assert_ne!(first, second);
+
+            let mat =
+                Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+            let one = t::func(first, t::integer().build()?, mat)?;
+            let two = t::func(second, t::string().build()?, mat)?;
+            Lib::expose(
+                vec![
+                    ("one".to_string(), one.into()),
+                    ("two".to_string(), two.into()),
+                ],
+                None,
+            )
+        })?;
+
+        let tg: common::typegraph::Typegraph = serde_json::from_str(&typegraph).unwrap();
+        let inputs = tg
+            .types
+            .iter()
+            .filter_map(|node| match node {
+                common::typegraph::TypeNode::Function { data, .. } => Some(data.input),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(inputs.len(), 2);
+        assert_eq!(inputs[0], inputs[1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_value() -> Result<()> {
+        let user = t::struct_()
+            .propx("age", t::integer().min(0).max(150))?
+            .propx("handle", t::string().pattern("^@[a-z]+$"))?
+            .propx(
+                "role",
+                t::string().enum_(vec!["admin".to_string(), "member".to_string()]),
+            )?
+            .propx("bio", t::optionalx(t::string())?)?
+            .build()?;
+        let value = |v: serde_json::Value| user.validate_value(&v);
+
+        value(serde_json::json!({ "age": 42, "handle": "@alice", "role": "admin" }))?;
+
+        let err = value(serde_json::json!({ "age": 200, "handle": "@alice", "role": "admin" }));
+        assert_eq!(
+            err,
+            Err(errors::TgError::from(
+                r#"value 200 at "<value>.age" does not satisfy the maximum"#
+            ))
+        );
+
+        let err = value(serde_json::json!({ "age": 42, "handle": "alice", "role": "admin" }));
+        assert_eq!(
+            err,
+            Err(errors::TgError::from(
+                r#"value "alice" at "<value>.handle" does not satisfy the pattern "^@[a-z]+$""#
+            ))
+        );
+
+        let err = value(serde_json::json!({ "age": 42, "role": "admin" }));
+        assert_eq!(
+            err,
+            Err(errors::TgError::from(
+                r#"missing required prop "handle" at "<value>""#
+            ))
+        );
+
+        let err = value(serde_json::json!({ "age": 42, "handle": "@alice", "role": "guest" }));
+        assert_eq!(
+            err,
+            Err(errors::TgError::from(
+                r#"value "guest" at "<value>.role" does not satisfy the enumeration"#
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_multiple_of() -> Result<()> {
+        use crate::validation::types::check_integer;
+
+        let tpe = t::float().multiple_of(0.1).build()?;
+        tpe.validate_value(&serde_json::json!(0.3))?;
+        tpe.validate_value(&serde_json::json!(1.2))?;
+        assert_eq!(
+            tpe.validate_value(&serde_json::json!(0.35)),
+            Err(errors::TgError::from(
+                r#"value 0.35 at "<value>" does not satisfy the multiple of"#
+            ))
+        );
+
+        // rejected by the builder, but the check must not panic either
+        let data = TypeInteger {
+            multiple_of: Some(0),
+            ..Default::default()
+        };
+        assert!(check_integer(&serde_json::json!(4), &data, "<value>").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deno_static_value() -> Result<()> {
+        use crate::wit::runtimes::MaterializerDenoStatic;
+
+        let static_value = |value: serde_json::Value, tpe: TypeId| {
+            Lib::register_deno_static(
+                MaterializerDenoStatic {
+                    value: value.to_string(),
+                },
+                tpe.into(),
+            )
+        };
+
+        let tpe = t::struct_()
+            .propx("age", t::integer().min(0).max(150))?
+            .build()?;
+        static_value(serde_json::json!({ "age": 42 }), tpe)?;
+        // the constraints are checked by the typegate
+        static_value(serde_json::json!({ "age": 200 }), tpe)?;
+        assert_eq!(
+            static_value(serde_json::json!({ "age": "42" }), tpe),
+            Err(errors::TgError::from(
+                r#"expected integer at "<V>.age", got: "42""#
+            ))
+        );
+
+        // values accepted by the typegate format validators
+        for (format, value) in crate::test_utils::FORMAT_SAMPLES {
+            let tpe = t::string().format(format).build()?;
+            static_value(serde_json::json!(value), tpe)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_path_params() -> Result<()> {
+        use crate::wit::runtimes::{
+            BaseMaterializer, HttpMethod, HttpRuntimeData, MaterializerHttpRequest,
+        };
+
+        let runtime = Lib::register_http_runtime(HttpRuntimeData {
+            endpoint: "https://example.com/api".to_string(),
+            cert_secret: None,
+            basic_auth_secret: None,
+        })?;
+        let get = |path: &str| {
+            Lib::http_request(
+                BaseMaterializer {
+                    runtime,
+                    effect: Effect::Read,
+                },
+                MaterializerHttpRequest {
+                    method: HttpMethod::Get,
+                    path: path.to_string(),
+                    content_type: None,
+                    header_prefix: None,
+                    query_fields: None,
+                    rename_fields: None,
+                    body_fields: None,
+                    auth_token_field: None,
+                },
+            )
+        };
+        let inp = t::struct_()
+            .prop("id", t::integer().build()?)
+            .prop("postId", t::integer().build()?)
+            .build()?;
+
+        let mat = get("/users/{id}/posts/{postId}")?;
+        t::func(inp, t::string().build()?, mat)?;
+
+        let mat = get("/users/{id}/posts/{post_id}")?;
+        let res = t::func(inp, t::string().build()?, mat).map(|_| ());
+        assert_eq!(
+            res,
+            Err(crate::validation::errors::unknown_path_param(
+                "post_id",
+                "/users/{id}/posts/{post_id}"
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_schema() -> Result<()> {
+        let age = t::integer().min(0).max(150).build()?;
+        assert_eq!(
+            age.to_json_schema()?,
+            serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 150,
+            })
+        );
+
+        let user = t::struct_()
+            .propx("email", t::string().format("email").describe("login"))?
+            .propx("nickname", t::optionalx(t::string().max(20))?)?
+            .named("JsonSchemaUser")
+            .build()?;
+        assert_eq!(
+            user.to_json_schema()?,
+            serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "JsonSchemaUser",
+                "type": "object",
+                "properties": {
+                    "email": {
+                        "type": "string",
+                        "format": "email",
+                        "description": "login",
+                    },
+                    "nickname": { "type": "string", "maxLength": 20 },
+                },
+                "required": ["email"],
+                "additionalProperties": false,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_runtime_config_order() -> Result<()> {
+        setup(None)?;
+        let value = t::integer()
+            .config("z", "1")
+            .config("a", "2")
+            .config("m", "3")
+            .build()?;
+        let keys = value
+            .as_type_def()?
+            .unwrap()
+            .base()
+            .runtime_config
+            .iter()
+            .flatten()
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["z", "a", "m"]);
+
+        let mat =
+            Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+        let inp = t::struct_().prop("value", value).build()?;
+        let one = t::func(inp, t::integer().build()?, mat)?;
+        Lib::expose(vec![("one".to_string(), one.into())], None)?;
+        let (typegraph, _) = Lib::serialize_typegraph(SerializeParams {
+            pretty: false,
+            ..Default::default()
+        })?;
+        assert!(typegraph.contains(r#""config":{"z":1,"a":2,"m":3}"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_required_scopes_provider() -> Result<()> {
+        let expose_scoped = || -> Result<TypeId> {
+            let mat =
+                Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+            let func = t::FuncBuilder::new(t::struct_().build()?, t::integer().build()?, mat)
+                .require_scopes(&["user:email"])
+                .build()?;
+            Lib::expose(vec![("one".to_string(), func.into())], None)?;
+            Ok(func)
+        };
+
+        setup(Some("no-provider"))?;
+        let func = expose_scoped()?;
+        assert_eq!(
+            Lib::serialize_typegraph(Default::default()).map(|_| ()),
+            Err(errors::missing_scopes_provider(&format!("func_{}", func.0)))
+        );
+
+        setup(Some("with-provider"))?;
+        let auth = Lib::oauth2_without_profiler("github".to_string(), "user:email".to_string())?;
+        Lib::add_raw_auth(auth)?;
+        expose_scoped()?;
+        Lib::serialize_typegraph(Default::default())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_description() -> Result<()> {
+        let serialize = |output: TypeId| -> Result<serde_json::Value> {
+            setup(None)?;
+            let mat =
+                Lib::register_deno_func(MaterializerDenoFunc::with_code("() => 12"), Effect::Read)?;
+            let one = t::func(t::struct_().build()?, output, mat)?;
+            Lib::expose(vec![("one".to_string(), one.into())], None)?;
+            let (typegraph, _) = Lib::serialize_typegraph(Default::default())?;
+            let tg: serde_json::Value = serde_json::from_str(&typegraph).unwrap();
+            let func_idx = tg["types"][0]["properties"]["one"].as_u64().unwrap() as usize;
+            let output_idx = tg["types"][func_idx]["output"].as_u64().unwrap() as usize;
+            Ok(tg["types"][output_idx].clone())
+        };
+
+        let count = t::integer()
+            .describe("number of items")
+            .config("min_count", "1")
+            .build()?;
+        let count = serialize(count)?;
+        assert_eq!(count["description"], "number of items");
+        assert_eq!(count["config"], serde_json::json!({ "min_count": 1 }));
+
+        // a user config entry named "description" is left as is
+        let count = serialize(t::integer().config("description", "42").build()?)?;
+        assert_eq!(count["description"], serde_json::Value::Null);
+        assert_eq!(count["config"], serde_json::json!({ "description": 42 }));
+
+        let count = t::integer().config(DESCRIPTION_CONFIG_KEY, "42").build()?;
+        assert_eq!(
+            serialize(count),
+            Err(errors::invalid_config_value(
+                DESCRIPTION_CONFIG_KEY,
+                "expected a string, got 42"
+            ))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_successful_serialization() -> Result<()> {
         Store::reset();
@@ -353,4 +353,45 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_default_value() -> Result<()> {
+        let type1 = t::struct_()
+            .propx("id", t::integer().as_id(true))?
+            .propx("age", t::integer().max(150).config("default", r#""42""#))?
+            .named("B")
+            .build()?;
+        assert_eq!(
+            Model::try_from(type1).err().map(|e| e.stack),
+            Some(vec![
+                r#"expected integer at "<default value>", got: "42""#.to_string()
+            ])
+        );
+
+        // the constraints are checked by the typegate
+        let type2 = t::struct_()
+            .propx("id", t::integer().as_id(true))?
+            .propx("age", t::integer().max(150).config("default", "200"))?
+            .named("C")
+            .build()?;
+        let model = Model::try_from(type2)?;
+        let age = model.props.get("age").unwrap().as_scalar().unwrap();
+        assert_eq!(age.default_value, Some(serde_json::json!(200)));
+
+        // values accepted by the typegate format validators
+        for (i, (format, value)) in crate::test_utils::FORMAT_SAMPLES.iter().enumerate() {
+            let default = serde_json::json!(value).to_string();
+            let tpe = t::struct_()
+                .propx("id", t::integer().as_id(true))?
+                .propx(
+                    "value",
+                    t::string().format(*format).config("default", default),
+                )?
+                .named(format!("Format{i}"))
+                .build()?;
+            Model::try_from(tpe)?;
+        }
+
+        Ok(())
+    }
 }
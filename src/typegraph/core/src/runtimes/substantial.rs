@@ -132,3 +132,46 @@ impl From<wit::runtimes::Workflow> for WorkflowMatData {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::TypeId;
+    use crate::wit::runtimes::{Guest, SubstantialRuntimeData, Workflow, WorkflowKind};
+    use crate::Lib;
+
+    #[test]
+    fn test_start_operation() -> Result<()> {
+        let runtime = Lib::register_substantial_runtime(SubstantialRuntimeData {
+            endpoint: "http://localhost:8000".to_string(),
+            basic_auth_secret: None,
+        })?;
+        let workflow = Workflow {
+            name: "saveAndSleep".to_string(),
+            file: "workflow.py".to_string(),
+            deps: vec![],
+            kind: WorkflowKind::Python,
+        };
+
+        let params = substantial_operation(
+            runtime,
+            SubstantialOperationData {
+                func_arg: None,
+                operation: SubstantialOperationType::Start(workflow),
+            },
+        )?;
+
+        let inp = TypeId(params.inp).as_struct()?;
+        assert!(inp.data.get_prop("name").is_some());
+
+        let mat = Store::get_materializer(params.mat)?;
+        assert_eq!(mat.runtime_id, runtime);
+        assert!(matches!(mat.effect, WitEffect::Create(false)));
+        assert!(matches!(
+            &mat.data,
+            crate::runtimes::MaterializerData::Substantial(data)
+                if matches!(&**data, SubstantialMaterializer::Start { workflow } if workflow.name == "saveAndSleep")
+        ));
+        Ok(())
+    }
+}
@@ -8,3 +8,49 @@ pub enum WasmMaterializer {
     ReflectedFunc(wit::MaterializerWasmReflectedFunc),
     WireHandler(wit::MaterializerWasmWireHandler),
 }
+
+#[cfg(test)]
+mod test {
+    use super::WasmMaterializer;
+    use crate::errors::Result;
+    use crate::global_store::Store;
+    use crate::runtimes::MaterializerData;
+    use crate::t::{self, ConcreteTypeBuilder, TypeBuilder};
+    use crate::test_utils::Effect;
+    use crate::wit::runtimes::{
+        BaseMaterializer, Guest, MaterializerWasmWireHandler, WasmRuntimeData,
+    };
+    use crate::Lib;
+
+    #[test]
+    fn test_wire_handler() -> Result<()> {
+        let runtime = Lib::register_wasm_wire_runtime(WasmRuntimeData {
+            wasm_artifact: "rust.wasm".to_string(),
+        })?;
+        let mat = Lib::from_wasm_wire_handler(
+            BaseMaterializer {
+                runtime,
+                effect: Effect::Read,
+            },
+            MaterializerWasmWireHandler {
+                func_name: "add".to_string(),
+            },
+        )?;
+
+        let inp = t::struct_()
+            .propx("a", t::integer())?
+            .propx("b", t::integer())?
+            .named("AddInput")
+            .build()?;
+        let func = t::func(inp, t::integer().build()?, mat)?;
+        assert!(func.is_func()?);
+
+        let mat = Store::get_materializer(mat)?;
+        assert_eq!(mat.runtime_id, runtime);
+        assert!(matches!(
+            &mat.data,
+            MaterializerData::Wasm(data) if matches!(**data, WasmMaterializer::WireHandler(_))
+        ));
+        Ok(())
+    }
+}
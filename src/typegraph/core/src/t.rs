@@ -1,20 +1,37 @@
 // Copyright Metatype OÜ, licensed under the Mozilla Public License Version 2.0.
 // SPDX-License-Identifier: MPL-2.0
 
-use crate::errors::Result;
+use crate::conversion::types::DESCRIPTION_CONFIG_KEY;
+use crate::errors::{self, ErrorContext, Result};
 use crate::global_store::{NameRegistration, Store};
-use crate::types::{ExtendedTypeBase, TypeDefExt, TypeId};
+use crate::types::{ExtendedTypeBase, TypeDef, TypeDefExt, TypeId, EXPECTED_VARIANT_ATTR};
+use crate::validation::types::ensure_registered_policies;
 use crate::wit::core::{
-    Guest, TypeBase, TypeEither, TypeFloat, TypeFunc, TypeInteger, TypeList, TypeOptional,
-    TypeString, TypeStruct, TypeUnion,
+    Guest, PolicyId, PolicyPerEffect, PolicySpec, TypeBase, TypeEither, TypeFloat, TypeFunc,
+    TypeInteger, TypeList, TypeOptional, TypeString, TypeStruct, TypeUnion,
 };
+use crate::wit::runtimes::Effect;
 
+/// Builders are meant to be used as a single chain ending with `build()`:
+/// each call to `build()` registers a new type in the store, so the
+/// resulting `TypeId` should be kept and reused instead of building again.
+/// Structurally identical types built twice are still deduplicated when the
+/// typegraph is serialized, as types are registered by hash.
 pub trait TypeBuilder {
+    #[must_use = "building a type registers it in the store; use the returned TypeId"]
     fn build(&self) -> Result<TypeId>;
 
     fn optional(&self) -> Result<OptionalBuilder> {
         Ok(optional(self.build()?))
     }
+
+    /// Optional type taking the given value when absent
+    fn defaulted<S: serde::Serialize>(&self, value: S) -> Result<TypeId> {
+        let mut builder = self.optional()?;
+        builder.data.default_item =
+            Some(serde_json::to_string(&value).map_err(|e| errors::TgError::from(e.to_string()))?);
+        builder.build()
+    }
 }
 
 impl<T> TypeBuilder for &mut T
@@ -32,6 +49,70 @@ impl TypeBuilder for TypeId {
     }
 }
 
+/// Builds the type of a successful result, e.g. of a nested `tstruct!`
+impl<T> TypeBuilder for Result<T>
+where
+    T: TypeBuilder,
+{
+    fn build(&self) -> Result<TypeId> {
+        self.as_ref().map_err(|e| e.clone())?.build()
+    }
+}
+
+/// `None` builds the unit type (an empty struct)
+impl<T> TypeBuilder for Option<T>
+where
+    T: TypeBuilder,
+{
+    fn build(&self) -> Result<TypeId> {
+        match self {
+            Some(builder) => builder.build(),
+            None => struct_().build(),
+        }
+    }
+}
+
+/// A single policy or a chain of policies, as accepted by `with_policy`
+pub trait AsPolicyChain {
+    fn as_chain(&self) -> Vec<PolicySpec>;
+}
+
+impl AsPolicyChain for PolicySpec {
+    fn as_chain(&self) -> Vec<PolicySpec> {
+        vec![self.clone()]
+    }
+}
+
+impl AsPolicyChain for PolicyId {
+    fn as_chain(&self) -> Vec<PolicySpec> {
+        vec![PolicySpec::Simple(*self)]
+    }
+}
+
+impl<P: AsPolicyChain> AsPolicyChain for [P] {
+    fn as_chain(&self) -> Vec<PolicySpec> {
+        self.iter().flat_map(|p| p.as_chain()).collect()
+    }
+}
+
+impl<P: AsPolicyChain, const N: usize> AsPolicyChain for [P; N] {
+    fn as_chain(&self) -> Vec<PolicySpec> {
+        self.as_slice().as_chain()
+    }
+}
+
+impl<P: AsPolicyChain> AsPolicyChain for Vec<P> {
+    fn as_chain(&self) -> Vec<PolicySpec> {
+        self.as_slice().as_chain()
+    }
+}
+
+impl<P: AsPolicyChain + ?Sized> AsPolicyChain for &P {
+    fn as_chain(&self) -> Vec<PolicySpec> {
+        (**self).as_chain()
+    }
+}
+
 #[allow(unused)]
 pub trait ConcreteTypeBuilder: TypeBuilder {
     fn base_mut(&mut self) -> &mut TypeBase;
@@ -52,6 +133,42 @@ pub trait ConcreteTypeBuilder: TypeBuilder {
         self
     }
 
+    fn with_policy(&mut self, policy_chain: impl AsPolicyChain) -> &mut Self {
+        self.xbase_mut().policies = policy_chain.as_chain();
+        self
+    }
+
+    fn with_policies(&mut self, policies: impl IntoIterator<Item = PolicySpec>) -> &mut Self {
+        self.with_policy(policies.into_iter().collect::<Vec<_>>())
+    }
+
+    fn with_policy_per_effect(
+        &mut self,
+        policies: impl IntoIterator<Item = (Effect, PolicyId)>,
+    ) -> Result<&mut Self> {
+        let mut per_effect = PolicyPerEffect {
+            read: None,
+            create: None,
+            update: None,
+            delete: None,
+        };
+        for (effect, policy_id) in policies {
+            let (name, slot) = match effect {
+                Effect::Read => ("read", &mut per_effect.read),
+                Effect::Create(_) => ("create", &mut per_effect.create),
+                Effect::Update(_) => ("update", &mut per_effect.update),
+                Effect::Delete(_) => ("delete", &mut per_effect.delete),
+            };
+            if slot.replace(policy_id).is_some() {
+                return Err(errors::duplicate_effect_policy(name));
+            }
+        }
+        Ok(self.with_policy(vec![PolicySpec::PerEffect(per_effect)]))
+    }
+
+    /// Injects a static value; like any injected prop, the field is hidden
+    /// from the exposed input and cannot be set by clients. Use a context
+    /// injection instead for values depending on the request.
     fn set_value<V: serde::ser::Serialize>(&mut self, val: V) -> &mut Self {
         self.inject(Injection::Static(InjectionData::SingleValue(SingleValue {
             value: serde_json::to_string(&val).unwrap(),
@@ -71,6 +188,59 @@ pub trait ConcreteTypeBuilder: TypeBuilder {
             .push((key.into(), value.into()));
         self
     }
+
+    /// Adds several config entries at once; unlike `config`, the values are
+    /// serialized to JSON here. Entries are appended in order, duplicate keys included.
+    fn config_many<K: Into<String>, V: serde::Serialize>(
+        &mut self,
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) -> &mut Self {
+        for (key, value) in entries {
+            self.config(key, serde_json::to_string(&value).unwrap());
+        }
+        self
+    }
+
+    /// Human-readable description, emitted in the typegraph type description
+    fn describe(&mut self, text: &str) -> &mut Self {
+        self.config(DESCRIPTION_CONFIG_KEY, serde_json::to_string(text).unwrap())
+    }
+
+    /// Marks the type as deprecated, with an optional reason
+    fn deprecated(&mut self, reason: Option<&str>) -> &mut Self {
+        self.config("deprecated", "true");
+        if let Some(reason) = reason {
+            self.config("deprecation_reason", serde_json::to_string(reason).unwrap());
+        }
+        self
+    }
+
+    /// Sample value for the docs; examples accumulate across calls
+    fn example<S: serde::Serialize>(&mut self, value: S) -> Result<&mut Self> {
+        let value = serde_json::to_value(value).unwrap();
+        let config = self
+            .base_mut()
+            .runtime_config
+            .get_or_insert_with(Default::default);
+        match config.iter_mut().find(|(k, _)| k == "examples") {
+            Some((_, examples)) => {
+                let mut values: Vec<serde_json::Value> = serde_json::from_str(examples)
+                    .map_err(|e| errors::invalid_config_value("examples", &e.to_string()))?;
+                values.push(value);
+                *examples = serde_json::to_string(&values).unwrap();
+            }
+            None => config.push((
+                "examples".to_string(),
+                serde_json::to_string(&[value]).unwrap(),
+            )),
+        }
+        Ok(self)
+    }
+
+    /// Stable JSON Schema `$id` for referencing the type from external schemas
+    fn schema_id(&mut self, id: &str) -> &mut Self {
+        self.config("schema_id", serde_json::to_string(id).unwrap())
+    }
 }
 
 #[derive(Default)]
@@ -83,6 +253,19 @@ pub fn boolean() -> BooleanBuilder {
     Default::default()
 }
 
+/// Escape hatch for loose JSON: accepts any value, null included, and is not
+/// checked any further by the typegate
+#[derive(Default)]
+pub struct AnyBuilder {
+    base: TypeBase,
+    extended_base: ExtendedTypeBase,
+}
+
+#[allow(dead_code)]
+pub fn any() -> AnyBuilder {
+    Default::default()
+}
+
 #[derive(Default)]
 pub struct IntegerBuilder {
     base: TypeBase,
@@ -106,28 +289,39 @@ impl Default for TypeInteger {
 
 impl IntegerBuilder {
     #[allow(dead_code)]
+    #[must_use]
     pub fn min(mut self, min: i32) -> Self {
         self.data.min = Some(min);
         self
     }
 
     #[allow(dead_code)]
+    #[must_use]
     pub fn max(mut self, max: i32) -> Self {
         self.data.max = Some(max);
         self
     }
 
     #[allow(dead_code)]
+    #[must_use]
     pub fn x_min(mut self, min: i32) -> Self {
         self.data.exclusive_minimum = Some(min);
         self
     }
 
     #[allow(dead_code)]
+    #[must_use]
     pub fn x_max(mut self, max: i32) -> Self {
         self.data.exclusive_maximum = Some(max);
         self
     }
+
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn multiple_of(mut self, value: i32) -> Self {
+        self.data.multiple_of = Some(value);
+        self
+    }
 }
 
 pub fn integer() -> IntegerBuilder {
@@ -157,28 +351,39 @@ impl Default for TypeFloat {
 
 impl FloatBuilder {
     #[allow(dead_code)]
+    #[must_use]
     pub fn min(mut self, min: f64) -> Self {
         self.data.min = Some(min);
         self
     }
 
     #[allow(dead_code)]
+    #[must_use]
     pub fn max(mut self, max: f64) -> Self {
         self.data.max = Some(max);
         self
     }
 
     #[allow(dead_code)]
+    #[must_use]
     pub fn x_min(mut self, min: f64) -> Self {
         self.data.exclusive_minimum = Some(min);
         self
     }
 
     #[allow(dead_code)]
+    #[must_use]
     pub fn x_max(mut self, max: f64) -> Self {
         self.data.exclusive_maximum = Some(max);
         self
     }
+
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn multiple_of(mut self, value: f64) -> Self {
+        self.data.multiple_of = Some(value);
+        self
+    }
 }
 
 pub fn float() -> FloatBuilder {
@@ -210,12 +415,40 @@ pub fn string() -> StringBuilder {
 }
 
 impl StringBuilder {
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn min(mut self, min: u32) -> Self {
+        self.data.min = Some(min);
+        self
+    }
+
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn max(mut self, max: u32) -> Self {
+        self.data.max = Some(max);
+        self
+    }
+
     #[allow(dead_code)]
     pub fn format(&mut self, format: impl Into<String>) -> &mut Self {
         self.data.format = Some(format.into());
         self
     }
 
+    #[allow(dead_code)]
+    pub fn pattern(&mut self, pattern: impl Into<String>) -> &mut Self {
+        self.data.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Ask the runtime to coerce empty strings to null on input.
+    /// This is only recorded in the runtime config: other constraints like
+    /// `min` are kept as is and still apply to the values that are not coerced.
+    #[allow(dead_code)]
+    pub fn empty_as_null(&mut self) -> &mut Self {
+        self.config("empty_as_null", "true")
+    }
+
     pub fn enum_(&mut self, values: Vec<String>) -> &mut Self {
         self.data.enumeration = Some(
             values
@@ -258,6 +491,16 @@ pub fn optionalx(item_builder: impl TypeBuilder) -> Result<OptionalBuilder> {
     Ok(optional(item_builder.build()?))
 }
 
+impl OptionalBuilder {
+    /// Whether an explicit null is accepted in addition to an absent value.
+    /// The wit optional type has no such flag, so it is recorded in the
+    /// runtime config for the runtime to enforce.
+    #[allow(dead_code)]
+    pub fn nullable(&mut self, value: bool) -> &mut Self {
+        self.config("nullable", value.to_string())
+    }
+}
+
 #[derive(Default)]
 pub struct ListBuilder {
     base: TypeBase,
@@ -291,6 +534,49 @@ pub fn listx(item_builder: impl TypeBuilder) -> Result<ListBuilder> {
     Ok(list(item_builder.build()?))
 }
 
+impl ListBuilder {
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn min(mut self, min: u32) -> Self {
+        self.data.min = Some(min);
+        self
+    }
+
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn max(mut self, max: u32) -> Self {
+        self.data.max = Some(max);
+        self
+    }
+
+    #[allow(dead_code)]
+    #[must_use]
+    pub fn non_empty(self) -> Self {
+        self.min(1)
+    }
+
+    /// Sets the item type, for lists created with `ListBuilder::default()`
+    /// before their item type is known; building a list without an item
+    /// type fails.
+    #[allow(dead_code)]
+    pub fn of(mut self, item_builder: impl TypeBuilder) -> Result<Self> {
+        self.data.of = item_builder.build()?.into();
+        Ok(self)
+    }
+}
+
+/// Fixed-length list standing for a tuple. The core has no tuple type, so the
+/// positions are not typed individually: every item is a union of the types.
+#[allow(dead_code)]
+pub fn tuple<B: TypeBuilder>(items: impl IntoIterator<Item = B>) -> Result<TypeId> {
+    let mut variants = vec![];
+    for item in items {
+        variants.push(item.build()?);
+    }
+    let len = variants.len() as u32;
+    list(union(variants).build()?).min(len).max(len).build()
+}
+
 #[derive(Default)]
 pub struct UnionBuilder {
     base: TypeBase,
@@ -317,6 +603,31 @@ impl UnionBuilder {
         self.add(ty.build()?);
         Ok(self)
     }
+
+    #[allow(dead_code)]
+    pub fn add_many(
+        &mut self,
+        tys: impl IntoIterator<Item = impl TypeBuilder>,
+    ) -> Result<&mut Self> {
+        for ty in tys {
+            self.addx(ty)?;
+        }
+        Ok(self)
+    }
+
+    /// Records the prop telling the variants apart, in the runtime config;
+    /// to be called once all the variants are added, each being a struct
+    /// with that prop.
+    #[allow(dead_code)]
+    pub fn discriminated(&mut self, tag_field: &str) -> Result<&mut Self> {
+        for variant in self.data.variants.iter() {
+            let variant = TypeId(*variant);
+            if variant.as_struct()?.data.get_prop(tag_field).is_none() {
+                return Err(errors::missing_discriminator(tag_field, &variant.repr()?));
+            }
+        }
+        Ok(self.config("discriminator", serde_json::to_string(tag_field).unwrap()))
+    }
 }
 
 pub fn union(variants: impl IntoIterator<Item = TypeId>) -> UnionBuilder {
@@ -328,6 +639,24 @@ pub fn union(variants: impl IntoIterator<Item = TypeId>) -> UnionBuilder {
     }
 }
 
+/// Union of fallible variants; the error reports the index of the first
+/// failing variant.
+#[allow(dead_code)]
+pub fn try_union<B: TypeBuilder>(
+    variants: impl IntoIterator<Item = Result<B>>,
+) -> Result<UnionBuilder> {
+    let variants = variants
+        .into_iter()
+        .enumerate()
+        .map(|(i, variant)| {
+            variant
+                .and_then(|builder| builder.build())
+                .with_context(|| format!("building variant #{i}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(union(variants))
+}
+
 macro_rules! unionx {
     [ $($ty:expr),* ] => {
         $crate::t::union(vec![$($ty.build()?),*])
@@ -340,6 +669,19 @@ macro_rules! unionx {
 use common::typegraph::{Injection, InjectionData, SingleValue};
 pub(crate) use unionx;
 
+/// Struct builder from `name: builder` pairs, the builders being built in
+/// order; evaluates to a `Result<StructBuilder>`, so it can be nested.
+#[allow(unused_macros)]
+macro_rules! tstruct {
+    { $($name:ident : $ty:expr),* $(,)? } => {
+        $crate::t::try_struct([
+            $((stringify!($name), $crate::t::TypeBuilder::build(&$ty))),*
+        ])
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use tstruct;
+
 #[derive(Default)]
 pub struct EitherBuilder {
     base: TypeBase,
@@ -410,6 +752,294 @@ pub fn struct_extends(ty: TypeId) -> Result<StructBuilder> {
     })
 }
 
+/// Struct with only the given props of a struct type, e.g. for a summary view
+#[allow(dead_code)]
+pub fn pick(ty: TypeId, fields: &[&str]) -> Result<StructBuilder> {
+    project(ty, fields, true)
+}
+
+/// Struct with the props of a struct type but the given ones, e.g. to derive
+/// an input type from a model without its id
+#[allow(dead_code)]
+pub fn omit(ty: TypeId, fields: &[&str]) -> Result<StructBuilder> {
+    project(ty, fields, false)
+}
+
+fn project(ty: TypeId, fields: &[&str], keep: bool) -> Result<StructBuilder> {
+    let mut builder = struct_extends(ty)?;
+    for field in fields {
+        if !builder.data.props.iter().any(|(name, _)| name == field) {
+            return Err(errors::unknown_key(field));
+        }
+    }
+    builder
+        .data
+        .props
+        .retain(|(name, _)| fields.contains(&name.as_str()) == keep);
+    Ok(builder)
+}
+
+/// Input either connecting an existing record of the model by id or creating
+/// a new one from the create input of the model.
+#[allow(dead_code)]
+pub fn connect_or_create(model: TypeId) -> Result<TypeId> {
+    let mut id_prop = None;
+    for (name, prop_id) in model.as_struct()?.iter_props() {
+        if matches!(prop_id.as_type_def()?, Some(t) if t.base().as_id) {
+            id_prop = Some((name.to_string(), prop_id));
+            break;
+        }
+    }
+    let Some((id_name, id_type)) = id_prop else {
+        return Err(errors::missing_id_prop(&model.repr()?));
+    };
+
+    let (create, _) = io_inputs(model)?;
+    either([
+        struct_()
+            .propx("connect", struct_().prop(id_name, id_type))?
+            .build()?,
+        struct_().prop("create", create).build()?,
+    ])
+    .build()
+}
+
+/// Struct builder from already built props, see `tstruct!`
+#[allow(dead_code)]
+pub fn try_struct<'a>(
+    props: impl IntoIterator<Item = (&'a str, Result<TypeId>)>,
+) -> Result<StructBuilder> {
+    let mut builder = struct_();
+    builder.try_props(props)?;
+    Ok(builder)
+}
+
+/// One-shot struct from a list of named prop builders
+#[allow(dead_code)]
+pub fn record<K: ToString, B: TypeBuilder>(
+    fields: impl IntoIterator<Item = (K, B)>,
+) -> Result<TypeId> {
+    let mut builder = struct_();
+    for (name, field) in fields {
+        builder.propx(name.to_string(), field)?;
+    }
+    builder.build()
+}
+
+/// Builders of the scalar types taking an enumeration
+pub trait EnumerableBuilder: TypeBuilder {
+    /// Sets the enumeration, failing on values not of the type
+    fn set_enumeration(&mut self, values: &[serde_json::Value]) -> Result<()>;
+}
+
+impl<T> EnumerableBuilder for &mut T
+where
+    T: EnumerableBuilder,
+{
+    fn set_enumeration(&mut self, values: &[serde_json::Value]) -> Result<()> {
+        (**self).set_enumeration(values)
+    }
+}
+
+impl EnumerableBuilder for IntegerBuilder {
+    fn set_enumeration(&mut self, values: &[serde_json::Value]) -> Result<()> {
+        let enumeration = values
+            .iter()
+            .map(|v| {
+                v.as_i64()
+                    .and_then(|v| i32::try_from(v).ok())
+                    .ok_or_else(|| errors::invalid_type("integer", &v.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.data.enumeration = Some(enumeration);
+        Ok(())
+    }
+}
+
+impl EnumerableBuilder for FloatBuilder {
+    fn set_enumeration(&mut self, values: &[serde_json::Value]) -> Result<()> {
+        let enumeration = values
+            .iter()
+            .map(|v| {
+                v.as_f64()
+                    .ok_or_else(|| errors::invalid_type("float", &v.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.data.enumeration = Some(enumeration);
+        Ok(())
+    }
+}
+
+impl EnumerableBuilder for StringBuilder {
+    fn set_enumeration(&mut self, values: &[serde_json::Value]) -> Result<()> {
+        let enumeration = values
+            .iter()
+            .map(|v| match v {
+                serde_json::Value::String(_) => Ok(v.to_string()),
+                _ => Err(errors::invalid_type("string", &v.to_string())),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.data.enumeration = Some(enumeration);
+        Ok(())
+    }
+}
+
+/// Attaches an enumeration to an integer, float or string builder; the type
+/// is registered once, with the name and injection set on the builder.
+#[allow(dead_code)]
+pub fn enum_of<V: serde::Serialize>(
+    mut ty: impl EnumerableBuilder,
+    values: impl IntoIterator<Item = V>,
+) -> Result<TypeId> {
+    let values = values
+        .into_iter()
+        .map(|v| serde_json::to_value(v).map_err(|e| errors::TgError::from(e.to_string())))
+        .collect::<Result<Vec<_>>>()?;
+    if values.is_empty() {
+        return Err(errors::empty_enumeration());
+    }
+    ty.set_enumeration(&values)?;
+    ty.build()
+}
+
+/// Single-valued enum type from a number or a string literal; booleans are
+/// not supported since boolean types have no enumeration.
+#[allow(dead_code)]
+pub fn literal(value: impl Into<serde_json::Value>) -> Result<TypeId> {
+    let value = value.into();
+    match &value {
+        serde_json::Value::Number(n) if n.as_i64().map_or(false, |v| i32::try_from(v).is_ok()) => {
+            enum_of(integer(), [value])
+        }
+        serde_json::Value::Number(_) => enum_of(float(), [value]),
+        serde_json::Value::String(_) => enum_of(string(), [value]),
+        _ => Err(errors::unsupported_literal(&value.to_string())),
+    }
+}
+
+/// Infers a type from a sample JSON value. Arrays are lists of their
+/// element type, or of a union when the elements have different shapes;
+/// empty arrays and nulls carry no type information and infer `any`.
+#[allow(dead_code)]
+pub fn infer(value: &serde_json::Value) -> Result<TypeId> {
+    use serde_json::Value;
+
+    match value {
+        Value::Bool(_) => boolean().build(),
+        Value::Number(n) if n.as_i64().map_or(false, |v| i32::try_from(v).is_ok()) => {
+            integer().build()
+        }
+        Value::Number(_) => float().build(),
+        Value::String(_) => string().build(),
+        Value::Object(map) => {
+            let mut builder = struct_();
+            for (key, value) in map {
+                let ty = infer(value).with_context(|| format!("inferring prop '{key}'"))?;
+                builder.prop(key, ty);
+            }
+            builder.build()
+        }
+        Value::Array(items) => {
+            let mut variants: indexmap::IndexMap<String, &Value> = Default::default();
+            for item in items {
+                variants.entry(shape_of(item)).or_insert(item);
+            }
+            let variants = variants
+                .into_values()
+                .map(infer)
+                .collect::<Result<Vec<_>>>()?;
+            match variants.len() {
+                0 => list(any().build()?).build(),
+                1 => list(variants[0]).build(),
+                _ => list(union(variants).build()?).build(),
+            }
+        }
+        Value::Null => any().build(),
+    }
+}
+
+/// Shape key of a JSON value: values with the same key infer the same type.
+fn shape_of(value: &serde_json::Value) -> String {
+    use serde_json::Value;
+
+    match value {
+        Value::Bool(_) => "boolean".to_string(),
+        Value::Number(n) if n.as_i64().map_or(false, |v| i32::try_from(v).is_ok()) => {
+            "integer".to_string()
+        }
+        Value::Number(_) => "float".to_string(),
+        Value::String(_) => "string".to_string(),
+        Value::Null => "null".to_string(),
+        Value::Object(map) => {
+            let props = map
+                .iter()
+                .map(|(k, v)| format!("{k:?}: {}", shape_of(v)))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", props.join(", "))
+        }
+        Value::Array(items) => {
+            // same order as the union variants from `infer`
+            let shapes = items
+                .iter()
+                .map(shape_of)
+                .collect::<indexmap::IndexSet<_>>();
+            format!("[{}]", shapes.into_iter().collect::<Vec<_>>().join(" | "))
+        }
+    }
+}
+
+/// Derives the create and update inputs of a model: the create input omits
+/// the generated props (id, `auto` or injected), the update input has all the
+/// props but the id as optional.
+#[allow(dead_code)]
+pub fn io_inputs(model: TypeId) -> Result<(TypeId, TypeId)> {
+    let mut create = struct_();
+    let mut update = struct_();
+    for (name, prop_id) in model.as_struct()?.iter_props() {
+        let type_def = prop_id.as_type_def()?;
+        if type_def.as_ref().map_or(false, |t| t.base().as_id) {
+            continue;
+        }
+        update.prop(name, optional(prop_id.resolve_optional()?).build()?);
+
+        let is_generated = type_def.as_ref().map_or(false, |t| {
+            t.x_base().injection.is_some()
+                || t.base()
+                    .runtime_config
+                    .iter()
+                    .flatten()
+                    .any(|(k, v)| k == "auto" && v == "true")
+        });
+        if !is_generated {
+            create.prop(name, prop_id);
+        }
+    }
+    Ok((create.build()?, update.build()?))
+}
+
+/// Order-by input of a model: each scalar prop maps to an optional sort
+/// direction; relations and nested structs are skipped. The directions are
+/// lowercase, "asc" or "desc", like the `_SortOrder` enum generated for the
+/// prisma runtime, as prisma only accepts these values.
+#[allow(dead_code)]
+pub fn order_by_input(model: TypeId) -> Result<TypeId> {
+    let direction =
+        optionalx(string().enum_(vec!["asc".to_string(), "desc".to_string()]))?.build()?;
+    let mut builder = struct_();
+    for (name, prop_id) in model.as_struct()?.iter_props() {
+        let is_scalar = matches!(
+            prop_id.resolve_optional()?.as_type_def()?,
+            Some(
+                TypeDef::Boolean(_) | TypeDef::Integer(_) | TypeDef::Float(_) | TypeDef::String(_)
+            )
+        );
+        if is_scalar {
+            builder.prop(name, direction);
+        }
+    }
+    builder.build()
+}
+
 impl StructBuilder {
     pub fn prop(&mut self, name: impl Into<String>, ty: TypeId) -> &mut Self {
         self.data.props.push((name.into(), ty.into()));
@@ -421,17 +1051,77 @@ impl StructBuilder {
         name: impl Into<String>,
         builder: impl TypeBuilder,
     ) -> Result<&mut Self> {
-        self.data.props.push((name.into(), builder.build()?.into()));
+        let name = name.into();
+        let ty = builder
+            .build()
+            .with_context(|| format!("building prop '{name}'"))?;
+        self.data.props.push((name, ty.into()));
         Ok(self)
     }
 
+    /// Adds a prop referencing a named union; the target is checked to be
+    /// a union when the ref is resolved.
+    #[allow(dead_code)]
+    pub fn prop_union_ref(
+        &mut self,
+        name: impl Into<String>,
+        union_ref: impl Into<String>,
+    ) -> Result<&mut Self> {
+        self.propx(name, ref_(union_ref).set(EXPECTED_VARIANT_ATTR, "union"))
+    }
+
+    /// Declares that at most one of the given props can be present; the
+    /// constraint is recorded in the runtime config, one group per call.
     #[allow(dead_code)]
+    pub fn exclusive(&mut self, fields: &[&str]) -> Result<&mut Self> {
+        for field in fields {
+            if !self.data.props.iter().any(|(name, _)| name == field) {
+                return Err(errors::unknown_key(field));
+            }
+        }
+        let config = self
+            .base
+            .runtime_config
+            .get_or_insert_with(Default::default);
+        match config.iter_mut().find(|(k, _)| k == "exclusive") {
+            Some((_, groups)) => {
+                let mut value: Vec<Vec<String>> = serde_json::from_str(groups)
+                    .map_err(|e| errors::invalid_config_value("exclusive", &e.to_string()))?;
+                value.push(fields.iter().map(|f| f.to_string()).collect());
+                *groups = serde_json::to_string(&value).unwrap();
+            }
+            None => config.push((
+                "exclusive".to_string(),
+                serde_json::to_string(&[fields]).unwrap(),
+            )),
+        }
+        Ok(self)
+    }
+
     pub fn props(&mut self, props: impl IntoIterator<Item = (String, TypeId)>) {
         self.data
             .props
             .extend(props.into_iter().map(|(name, ty)| (name, ty.into())));
     }
 
+    /// Adds many props at once; nothing is added if any of them failed to
+    /// build, and the error reports the first failing prop.
+    #[allow(dead_code)]
+    pub fn try_props<'a>(
+        &mut self,
+        props: impl IntoIterator<Item = (&'a str, Result<TypeId>)>,
+    ) -> Result<&mut Self> {
+        let props = props
+            .into_iter()
+            .map(|(name, ty)| {
+                ty.map(|ty| (name.to_string(), ty.into()))
+                    .with_context(|| format!("building prop '{name}'"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.data.props.extend(props);
+        Ok(self)
+    }
+
     pub fn min(&mut self, min: u32) -> &mut Self {
         self.data.min = Some(min);
         self
@@ -442,11 +1132,61 @@ impl StructBuilder {
         self.data.max = Some(max);
         self
     }
+
+    /// Sorts the props added so far by name, for stable serialized diffs;
+    /// props are serialized in insertion order otherwise.
+    #[allow(dead_code)]
+    pub fn sort_props(&mut self) -> &mut Self {
+        self.data.props.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self
+    }
+
+    /// Makes the props added so far optional, like `Partial<T>` in TypeScript;
+    /// nested structs are made partial as well if `deep` is set. Props that
+    /// are already optional are kept as is.
+    #[allow(dead_code)]
+    pub fn partial(&mut self, deep: bool) -> Result<&mut Self> {
+        self.data.props = self
+            .data
+            .props
+            .iter()
+            .map(|(name, ty)| -> Result<_> {
+                Ok((name.clone(), partial_prop(TypeId(*ty), deep)?.into()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Makes the optional props added so far required, the inverse of
+    /// `partial` for the top-level props; other props are kept as is.
+    #[allow(dead_code)]
+    pub fn required(&mut self) -> Result<&mut Self> {
+        self.data.props = self
+            .data
+            .props
+            .iter()
+            .map(|(name, ty)| -> Result<_> {
+                match TypeId(*ty).as_type_def()? {
+                    Some(TypeDef::Optional(inner)) => Ok((name.clone(), inner.data.of)),
+                    _ => Ok((name.clone(), *ty)),
+                }
+            })
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+}
+
+fn partial_prop(ty: TypeId, deep: bool) -> Result<TypeId> {
+    let item = match ty.as_type_def()? {
+        Some(TypeDef::Optional(_)) => return Ok(ty),
+        Some(TypeDef::Struct(_)) if deep => struct_extends(ty)?.partial(true)?.build()?,
+        _ => ty,
+    };
+    optional(item).build()
 }
 
 #[derive(Default)]
 pub struct FuncBuilder {
-    #[allow(dead_code)]
     base: TypeBase,
     data: TypeFunc,
 }
@@ -464,18 +1204,91 @@ impl Default for TypeFunc {
     }
 }
 
-#[allow(dead_code)]
-pub fn func(inp: TypeId, out: TypeId, mat: u32) -> Result<TypeId> {
-    FuncBuilder {
-        data: TypeFunc {
-            inp: inp.into(),
-            out: out.into(),
-            mat,
+impl FuncBuilder {
+    pub fn new(inp: TypeId, out: TypeId, mat: u32) -> Self {
+        Self {
+            data: TypeFunc {
+                inp: inp.into(),
+                out: out.into(),
+                mat,
+                ..Default::default()
+            },
             ..Default::default()
-        },
-        ..Default::default()
+        }
+    }
+
+    /// Batch version of the func: func inputs must be structs, so the original
+    /// inputs are passed as a list in the `items` prop, and the output is a
+    /// list of the original outputs. The materializer is kept as is and must
+    /// handle the batched input.
+    #[allow(dead_code)]
+    pub fn batched(&self) -> Result<FuncBuilder> {
+        let inp = struct_()
+            .propx("items", list(self.data.inp.into()))?
+            .build()?;
+        let out = list(self.data.out.into()).build()?;
+        Ok(Self::new(inp, out, self.data.mat))
+    }
+
+    /// Effect of the materializer, e.g. to expose the func as a query or
+    /// a mutation; materializers are always registered with an effect
+    /// (`read` by default).
+    #[allow(dead_code)]
+    pub fn effect(&self) -> Result<Effect> {
+        Ok(Store::get_materializer(self.data.mat)?.effect)
+    }
+
+    fn config<V: serde::Serialize + ?Sized>(mut self, key: &str, value: &V) -> Self {
+        self.base
+            .runtime_config
+            .get_or_insert_with(Default::default)
+            .push((key.to_string(), serde_json::to_string(value).unwrap()));
+        self
+    }
+
+    /// Span name for the runtime to use when tracing the calls of this func
+    #[allow(dead_code)]
+    pub fn trace_name(self, name: &str) -> Self {
+        self.config("trace_name", name)
+    }
+
+    /// Weight of a call in the typegraph rate limiter, set in the `rate` of
+    /// the typegraph init params; calls are not counted without a limiter.
+    #[allow(dead_code)]
+    pub fn rate_weight(mut self, weight: u32) -> Self {
+        self.data.rate_weight = Some(weight);
+        self
+    }
+
+    /// Counts the calls of the func in the rate limiter instead of the
+    /// number of returned items
+    #[allow(dead_code)]
+    pub fn rate_calls(mut self, rate_calls: bool) -> Self {
+        self.data.rate_calls = rate_calls;
+        self
+    }
+
+    /// OAuth scopes the caller must have been granted, recorded for the
+    /// runtime to check against the auth provider
+    #[allow(dead_code)]
+    pub fn require_scopes(self, scopes: &[&str]) -> Self {
+        self.config("required_scopes", scopes)
     }
-    .build()
+}
+
+#[allow(dead_code)]
+pub fn func(inp: TypeId, out: TypeId, mat: u32) -> Result<TypeId> {
+    FuncBuilder::new(inp, out, mat).build()
+}
+
+/// Func echoing its input through the predefined deno `identity` function:
+/// the output type must be equal to the input type.
+#[allow(dead_code)]
+pub fn identity(inp: TypeId, out: TypeId) -> Result<TypeId> {
+    let mat = Store::get_predefined_deno_function("identity".to_string())?;
+    let builder = FuncBuilder::new(inp, out, mat);
+    builder.data.validate()?;
+    builder.build()
 }
 
 pub struct RefBuilder {
@@ -498,12 +1311,23 @@ pub fn ref_(name: impl Into<String>) -> RefBuilder {
     }
 }
 
+/// Reference to a named type, for reusing it explicitly instead of passing
+/// its id around; anonymous types cannot be referenced.
+#[allow(dead_code)]
+pub fn ref_to(type_id: TypeId) -> Result<RefBuilder> {
+    match type_id.name()? {
+        Some(name) => Ok(ref_(name)),
+        None => Err(errors::anonymous_type_ref(&type_id.repr()?)),
+    }
+}
+
 macro_rules! impl_type_builder {
     ( $ty:ty, $build:ident ) => {
         impl TypeBuilder for $ty {
             fn build(&self) -> Result<TypeId> {
                 let res = $crate::Lib::$build(self.data.clone(), self.base.clone())?;
                 if !self.extended_base.is_empty() {
+                    ensure_registered_policies(&self.extended_base.policies)?;
                     let type_def = TypeId(res).as_type_def()?.unwrap();
                     Store::register_type_def(
                         move |id| type_def.with_x_base(id, self.extended_base.clone()),
@@ -526,20 +1350,12 @@ macro_rules! impl_type_builder {
             }
         }
     };
-
-    ( $ty:ty, $build:ident, true ) => {
-        impl TypeBuilder for $ty {
-            fn build(&self) -> Result<TypeId> {
-                let builder = self.clone();
-                Ok($crate::Lib::$build(builder.data.clone())?.into())
-            }
-        }
-    };
 }
 impl TypeBuilder for BooleanBuilder {
     fn build(&self) -> Result<TypeId> {
         let res = crate::Lib::booleanb(self.base.clone())?;
         if !self.extended_base.is_empty() {
+            ensure_registered_policies(&self.extended_base.policies)?;
             let type_def = TypeId(res).as_type_def()?.unwrap();
             Store::register_type_def(
                 move |id| type_def.with_x_base(id, self.extended_base.clone()),
@@ -562,6 +1378,32 @@ impl ConcreteTypeBuilder for BooleanBuilder {
     }
 }
 
+impl TypeBuilder for AnyBuilder {
+    fn build(&self) -> Result<TypeId> {
+        let res = crate::Lib::anyb(self.base.clone())?;
+        if !self.extended_base.is_empty() {
+            ensure_registered_policies(&self.extended_base.policies)?;
+            let type_def = TypeId(res).as_type_def()?.unwrap();
+            Store::register_type_def(
+                move |id| type_def.with_x_base(id, self.extended_base.clone()),
+                NameRegistration(false),
+            )
+        } else {
+            Ok(res.into())
+        }
+    }
+}
+
+impl ConcreteTypeBuilder for AnyBuilder {
+    fn base_mut(&mut self) -> &mut TypeBase {
+        &mut self.base
+    }
+
+    fn xbase_mut(&mut self) -> &mut ExtendedTypeBase {
+        &mut self.extended_base
+    }
+}
+
 impl_type_builder!(IntegerBuilder, integerb);
 impl_type_builder!(FloatBuilder, floatb);
 impl_type_builder!(OptionalBuilder, optionalb);
@@ -570,10 +1412,864 @@ impl_type_builder!(ListBuilder, listb);
 impl_type_builder!(UnionBuilder, unionb);
 impl_type_builder!(EitherBuilder, eitherb);
 impl_type_builder!(StructBuilder, structb);
-impl_type_builder!(FuncBuilder, funcb, true);
+
+impl TypeBuilder for FuncBuilder {
+    fn build(&self) -> Result<TypeId> {
+        let res = crate::Lib::funcb(self.data.clone())?;
+        if self.base.runtime_config.is_some() {
+            let type_def = TypeId(res).as_type_def()?.unwrap();
+            Store::register_type_def(
+                move |id| type_def.with_base(id, self.base.clone()),
+                NameRegistration(true),
+            )
+        } else {
+            Ok(res.into())
+        }
+    }
+}
 
 impl TypeBuilder for RefBuilder {
     fn build(&self) -> Result<TypeId> {
         Ok(crate::Lib::refb(self.name.clone(), self.attributes.clone())?.into())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_option_builder() -> Result<()> {
+        let some = Some(integer()).build()?;
+        assert!(matches!(some.as_type_def()?, Some(TypeDef::Integer(_))));
+
+        let none = None::<IntegerBuilder>.build()?;
+        match none.as_type_def()? {
+            Some(TypeDef::Struct(inner)) => assert!(inner.data.props.is_empty()),
+            _ => panic!("expected an empty struct, got {}", none.repr()?),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_as_null() -> Result<()> {
+        let mut builder = string();
+        builder.data.min = Some(1);
+        let tpe = builder.empty_as_null().build()?;
+
+        let Some(TypeDef::String(inner)) = tpe.as_type_def()? else {
+            panic!("expected a string type");
+        };
+        assert_eq!(inner.data.min, Some(1));
+        assert_eq!(
+            inner.base.runtime_config,
+            Some(vec![("empty_as_null".to_string(), "true".to_string())])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_func_trace_name() -> Result<()> {
+        let tpe = FuncBuilder::new(struct_().build()?, integer().build()?, 0)
+            .trace_name("getUser")
+            .build()?;
+        let Some(TypeDef::Func(inner)) = tpe.as_type_def()? else {
+            panic!("expected a func type");
+        };
+        assert_eq!(
+            inner.base.runtime_config,
+            Some(vec![("trace_name".to_string(), "\"getUser\"".to_string())])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity() -> Result<()> {
+        let inp = struct_().prop("a", integer().build()?).build()?;
+        let func = identity(inp, inp)?;
+        assert!(matches!(func.as_type_def()?, Some(TypeDef::Func(_))));
+
+        let out = struct_().prop("a", string().build()?).build()?;
+        assert_eq!(
+            identity(inp, out),
+            Err(crate::validation::errors::invalid_output_type_predefined(
+                "identity",
+                &inp.repr()?,
+                &out.repr()?
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_non_empty() -> Result<()> {
+        let tpe = listx(string())?.max(10).non_empty().build()?;
+        let Some(TypeDef::List(inner)) = tpe.as_type_def()? else {
+            panic!("expected a list type");
+        };
+        assert_eq!(inner.data.min, Some(1));
+        assert_eq!(inner.data.max, Some(10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_record() -> Result<()> {
+        let tpe = record([("a", integer()), ("b", integer().min(1))])?;
+        let inner = tpe.as_struct()?;
+        let keys = inner.iter_props().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["a", "b"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prop_error_context() {
+        let res = struct_()
+            .propx("email", integer().min(5).max(1))
+            .map(|_| ());
+        let err = res.unwrap_err();
+        assert_eq!(
+            err.stack,
+            vec![
+                errors::invalid_max_value().stack[0].clone(),
+                "building prop 'email'".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_props() -> Result<()> {
+        let tpe = struct_()
+            .prop("name", string().build()?)
+            .prop("age", integer().build()?)
+            .prop("id", integer().build()?)
+            .sort_props()
+            .build()?;
+        let keys = tpe
+            .as_struct()?
+            .iter_props()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["age", "id", "name"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial() -> Result<()> {
+        let address = struct_().prop("city", string().build()?).build()?;
+        let user = struct_()
+            .prop("name", string().build()?)
+            .prop("address", address)
+            .build()?;
+        let is_optional = |ty: TypeId| matches!(ty.as_type_def(), Ok(Some(TypeDef::Optional(_))));
+        let item = |ty: TypeId| match ty.as_type_def()? {
+            Some(TypeDef::Optional(inner)) => Ok(TypeId(inner.data.of)),
+            _ => Err(errors::TgError::from("expected an optional type")),
+        };
+
+        let partial = struct_extends(user)?.partial(false)?.build()?.as_struct()?;
+        assert!(partial.iter_props().all(|(_, ty)| is_optional(ty)));
+        let nested = item(partial.data.get_prop("address").unwrap())?;
+        assert_eq!(nested, address);
+
+        let deep = struct_extends(user)?.partial(true)?.build()?.as_struct()?;
+        let nested = item(deep.data.get_prop("address").unwrap())?.as_struct()?;
+        assert!(is_optional(nested.data.get_prop("city").unwrap()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_required() -> Result<()> {
+        let name = string().build()?;
+        let age = integer().build()?;
+        let input = struct_()
+            .prop("name", name)
+            .prop("age", optional(age).build()?)
+            .build()?;
+        let output = struct_extends(input)?.required()?.build()?.as_struct()?;
+        assert_eq!(output.data.get_prop("name"), Some(name));
+        assert_eq!(output.data.get_prop("age"), Some(age));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pick_omit() -> Result<()> {
+        let user = struct_()
+            .prop("id", integer().build()?)
+            .prop("name", string().build()?)
+            .prop("email", string().build()?)
+            .build()?;
+        let keys = |ty: TypeId| -> Result<Vec<String>> {
+            Ok(ty
+                .as_struct()?
+                .iter_props()
+                .map(|(k, _)| k.to_string())
+                .collect())
+        };
+
+        let summary = pick(user, &["email", "name"])?.build()?;
+        assert_eq!(keys(summary)?, vec!["name", "email"]);
+
+        let input = omit(user, &["id"])?.build()?;
+        assert_eq!(keys(input)?, vec!["name", "email"]);
+
+        let res = omit(user, &["password"]).map(|_| ());
+        assert_eq!(res, Err(errors::unknown_key("password")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tstruct_macro() -> Result<()> {
+        let (id, name) = (integer().build()?, string().build()?);
+        let from_macro = tstruct! { id: id, name: name }?.build()?;
+        let by_hand = struct_().prop("id", id).prop("name", name).build()?;
+        assert_eq!(
+            from_macro.as_struct()?.data.props,
+            by_hand.as_struct()?.data.props
+        );
+
+        let nested = tstruct! {
+            id: integer(),
+            author: tstruct! { name: string().min(1) },
+        }?
+        .build()?;
+        let author = nested.as_struct()?.data.get_prop("author").unwrap();
+        let keys = author
+            .as_struct()?
+            .iter_props()
+            .map(|(k, _)| k.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["name"]);
+
+        let err = tstruct! { id: integer().min(5).max(1) }
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(
+            err.stack,
+            vec![
+                errors::invalid_max_value().stack[0].clone(),
+                "building prop 'id'".to_string()
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_props() -> Result<()> {
+        let tpe = struct_()
+            .try_props([
+                ("a", integer().build()),
+                ("b", float().build()),
+                ("c", string().build()),
+                ("d", boolean().build()),
+                ("e", optional(integer().build()?).build()),
+            ])?
+            .build()?;
+        let keys = tpe
+            .as_struct()?
+            .iter_props()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["a", "b", "c", "d", "e"]);
+
+        let mut builder = struct_();
+        let err = builder
+            .try_props([
+                ("a", integer().build()),
+                ("b", integer().build()),
+                ("c", integer().min(5).max(1).build()),
+                ("d", integer().build()),
+            ])
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(
+            err.stack,
+            vec![
+                errors::invalid_max_value().stack[0].clone(),
+                "building prop 'c'".to_string()
+            ]
+        );
+        assert!(builder.data.props.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_infer() -> Result<()> {
+        let sample = serde_json::json!({
+            "name": "Alice",
+            "age": 42,
+            "tags": ["a", "b"],
+            "scores": [1, 2.5],
+            "address": { "city": "Paris", "zip": 75001 },
+        });
+        let tpe = infer(&sample)?;
+        let inner = tpe.as_struct()?;
+        let mut keys = inner.iter_props().map(|(k, _)| k).collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["address", "age", "name", "scores", "tags"]);
+
+        let prop = |name: &str| inner.data.get_prop(name).unwrap();
+        assert!(matches!(
+            prop("age").as_type_def()?,
+            Some(TypeDef::Integer(_))
+        ));
+        let tags = prop("tags").as_list()?.data.of;
+        assert!(matches!(
+            TypeId(tags).as_type_def()?,
+            Some(TypeDef::String(_))
+        ));
+        let scores = prop("scores").as_list()?.data.of;
+        let Some(TypeDef::Union(scores)) = TypeId(scores).as_type_def()? else {
+            panic!("expected a union of the element types");
+        };
+        assert_eq!(scores.data.variants.len(), 2);
+        let address = prop("address").as_struct()?;
+        assert!(matches!(
+            address.data.get_prop("zip").unwrap().as_type_def()?,
+            Some(TypeDef::Integer(_))
+        ));
+
+        assert_eq!(
+            shape_of(&serde_json::json!([1, "a", 2])),
+            "[integer | string]"
+        );
+        let tpe = infer(&serde_json::json!([[1, "a", 2], [1, "a"]]))?;
+        let item = TypeId(tpe.as_list()?.data.of);
+        let Some(TypeDef::List(item)) = item.as_type_def()? else {
+            panic!("expected a single list variant");
+        };
+        let Some(TypeDef::Union(item)) = TypeId(item.data.of).as_type_def()? else {
+            panic!("expected a union of the element types");
+        };
+        assert_eq!(item.data.variants.len(), 2);
+
+        let tpe = infer(&serde_json::json!({ "items": [] }))?;
+        let items = tpe.as_struct()?.data.get_prop("items").unwrap();
+        let item = items.as_list()?.data.of;
+        assert!(matches!(TypeId(item).as_type_def()?, Some(TypeDef::Any(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_any() -> Result<()> {
+        let tpe = list(any().build()?).build()?;
+        let item = TypeId(tpe.as_list()?.data.of);
+        assert_eq!(item.repr()?, format!("any(#{})", item.0));
+
+        let payload = any().named("Payload").optional()?.build()?;
+        let payload = payload.resolve_optional()?;
+        assert_eq!(payload.name()?, Some("Payload".to_string()));
+        assert!(matches!(payload.as_type_def()?, Some(TypeDef::Any(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_union() -> Result<()> {
+        let tpe = try_union([integer().build(), string().build(), boolean().build()])?.build()?;
+        let Some(TypeDef::Union(inner)) = tpe.as_type_def()? else {
+            panic!("expected a union");
+        };
+        assert_eq!(inner.data.variants.len(), 3);
+
+        let err = try_union([
+            integer().build(),
+            integer().min(5).max(1).build(),
+            string().build(),
+        ])
+        .map(|_| ())
+        .unwrap_err();
+        assert_eq!(
+            err.stack,
+            vec![
+                errors::invalid_max_value().stack[0].clone(),
+                "building variant #1".to_string()
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_union_add_many() -> Result<()> {
+        let variant = |kind: &str| -> Result<TypeId> {
+            struct_()
+                .propx("kind", string().enum_(vec![kind.to_string()]))?
+                .build()
+        };
+        let mut builder = union([]);
+        builder.add_many([variant("a")?, variant("b")?, variant("c")?])?;
+        let tpe = builder.build()?;
+        let Some(TypeDef::Union(inner)) = tpe.as_type_def()? else {
+            panic!("expected a union");
+        };
+        assert_eq!(inner.data.variants.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_discriminated_union() -> Result<()> {
+        let cat = struct_()
+            .prop("type", string().build()?)
+            .prop("meow", boolean().build()?)
+            .build()?;
+        let dog = struct_()
+            .prop("type", string().build()?)
+            .prop("bark", boolean().build()?)
+            .build()?;
+        let pet = union([cat, dog]).discriminated("type")?.build()?;
+        let config = pet.as_type_def()?.unwrap().base().runtime_config.clone();
+        assert_eq!(
+            config,
+            Some(vec![("discriminator".to_string(), r#""type""#.to_string())])
+        );
+
+        let res = union([cat, dog]).discriminated("meow").map(|_| ());
+        assert_eq!(
+            res,
+            Err(errors::missing_discriminator("meow", &dog.repr()?))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_id() -> Result<()> {
+        let tpe = string()
+            .schema_id("https://example.com/schemas/email")
+            .build()?;
+        let config = tpe.as_type_def()?.unwrap().base().runtime_config.clone();
+        assert_eq!(
+            config,
+            Some(vec![(
+                "schema_id".to_string(),
+                "\"https://example.com/schemas/email\"".to_string()
+            )])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_order_by_input() -> Result<()> {
+        let model = struct_()
+            .prop("name", string().build()?)
+            .propx("age", optionalx(integer())?)?
+            .propx("tags", listx(string())?)?
+            .build()?;
+        let order_by = order_by_input(model)?.as_struct()?;
+        let props = order_by.iter_props().collect::<Vec<_>>();
+        assert_eq!(
+            props.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec!["name", "age"]
+        );
+        for (_, prop_id) in props {
+            let Some(TypeDef::String(direction)) = prop_id.resolve_optional()?.as_type_def()?
+            else {
+                panic!("expected an optional string enum");
+            };
+            assert_eq!(
+                direction.data.enumeration,
+                Some(vec!["\"asc\"".to_string(), "\"desc\"".to_string()])
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_of() -> Result<()> {
+        let values = vec!["a".to_string(), "b".to_string()];
+        let tpe = enum_of(string(), &values)?;
+        let Some(TypeDef::String(inner)) = tpe.as_type_def()? else {
+            panic!("expected a string type");
+        };
+        assert_eq!(
+            inner.data.enumeration,
+            Some(vec!["\"a\"".to_string(), "\"b\"".to_string()])
+        );
+
+        let tpe = enum_of(integer().min(0), [1, 2, 3])?;
+        let Some(TypeDef::Integer(inner)) = tpe.as_type_def()? else {
+            panic!("expected an integer type");
+        };
+        assert_eq!(inner.data.enumeration, Some(vec![1, 2, 3]));
+        assert_eq!(inner.data.min, Some(0));
+
+        assert_eq!(
+            enum_of(integer(), Vec::<i32>::new()),
+            Err(errors::empty_enumeration())
+        );
+        assert_eq!(
+            enum_of(integer(), ["a"]),
+            Err(errors::invalid_type("integer", "\"a\""))
+        );
+
+        let tpe = enum_of(string().named("Color"), ["red", "green"])?;
+        assert_eq!(tpe.name()?, Some("Color".to_string()));
+        assert_eq!(Store::get_type_by_name("Color"), Some(tpe));
+        Ok(())
+    }
+
+    #[test]
+    fn test_literal() -> Result<()> {
+        let tpe = literal(42)?;
+        let Some(TypeDef::Integer(inner)) = tpe.as_type_def()? else {
+            panic!("expected an integer type");
+        };
+        assert_eq!(inner.data.enumeration, Some(vec![42]));
+
+        let tpe = literal(1.5)?;
+        let Some(TypeDef::Float(inner)) = tpe.as_type_def()? else {
+            panic!("expected a float type");
+        };
+        assert_eq!(inner.data.enumeration, Some(vec![1.5]));
+
+        let tpe = literal("GET")?;
+        let Some(TypeDef::String(inner)) = tpe.as_type_def()? else {
+            panic!("expected a string type");
+        };
+        assert_eq!(inner.data.enumeration, Some(vec!["\"GET\"".to_string()]));
+
+        assert_eq!(literal(true), Err(errors::unsupported_literal("true")));
+        assert_eq!(
+            literal(serde_json::Value::Null),
+            Err(errors::unsupported_literal("null"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclusive_props() -> Result<()> {
+        let mut builder = struct_();
+        builder
+            .propx("email", optionalx(string())?)?
+            .propx("phone", optionalx(string())?)?
+            .propx("name", string())?;
+        assert_eq!(
+            builder.exclusive(&["email", "address"]).map(|_| ()),
+            Err(errors::unknown_key("address"))
+        );
+
+        let tpe = builder.exclusive(&["email", "phone"])?.build()?;
+        let config = tpe.as_type_def()?.unwrap().base().runtime_config.clone();
+        assert_eq!(
+            config,
+            Some(vec![(
+                "exclusive".to_string(),
+                r#"[["email","phone"]]"#.to_string()
+            )])
+        );
+
+        let mut builder = struct_();
+        builder
+            .propx("email", string())?
+            .config("exclusive", "true");
+        assert!(matches!(
+            builder.exclusive(&["email"]),
+            Err(e) if e.stack[0].starts_with("invalid value for config 'exclusive'")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_optional_nullable() -> Result<()> {
+        let item = string().build()?;
+        let absent_only = optional(item).nullable(false).build()?;
+        let nullable = optional(item).nullable(true).build()?;
+        let config = |id: TypeId| -> Result<_> {
+            Ok(id.as_type_def()?.unwrap().base().runtime_config.clone())
+        };
+        assert_eq!(
+            config(absent_only)?,
+            Some(vec![("nullable".to_string(), "false".to_string())])
+        );
+        assert_eq!(
+            config(nullable)?,
+            Some(vec![("nullable".to_string(), "true".to_string())])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuple() -> Result<()> {
+        let (a, b) = (string().build()?, integer().build()?);
+        let tpe = tuple([a, b])?;
+        let Some(TypeDef::List(inner)) = tpe.as_type_def()? else {
+            panic!("expected a list type");
+        };
+        assert_eq!((inner.data.min, inner.data.max), (Some(2), Some(2)));
+        let Some(TypeDef::Union(items)) = TypeId(inner.data.of).as_type_def()? else {
+            panic!("expected a union of the item types");
+        };
+        assert_eq!(items.data.variants, vec![a.0, b.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batched_func() -> Result<()> {
+        let (inp, out) = (
+            struct_().prop("a", integer().build()?).build()?,
+            string().build()?,
+        );
+        let batched = FuncBuilder::new(inp, out, 0).batched()?.build()?;
+        let Some(TypeDef::Func(inner)) = batched.as_type_def()? else {
+            panic!("expected a func type");
+        };
+
+        let items = TypeId(inner.data.inp)
+            .as_struct()?
+            .data
+            .get_prop("items")
+            .unwrap();
+        assert_eq!(items.as_list()?.data.of, inp.0);
+        assert_eq!(TypeId(inner.data.out).as_list()?.data.of, out.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_optional_union() -> Result<()> {
+        let variants = vec![string().build()?, integer().build()?];
+        let tpe = union(variants.clone()).optional()?.build()?;
+        let Some(TypeDef::Optional(inner)) = tpe.as_type_def()? else {
+            panic!("expected an optional type");
+        };
+        let Some(TypeDef::Union(union)) = TypeId(inner.data.of).as_type_def()? else {
+            panic!("expected an optional union");
+        };
+        assert_eq!(
+            union.data.variants,
+            variants.iter().map(|v| v.0).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_func_required_scopes() -> Result<()> {
+        let tpe = FuncBuilder::new(struct_().build()?, integer().build()?, 0)
+            .require_scopes(&["read:user", "user:email"])
+            .build()?;
+        let config = tpe.as_type_def()?.unwrap().base().runtime_config.clone();
+        assert_eq!(
+            config,
+            Some(vec![(
+                "required_scopes".to_string(),
+                r#"["read:user","user:email"]"#.to_string()
+            )])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_defaulted() -> Result<()> {
+        let item = string().build()?;
+        let tpe = item.defaulted("x")?;
+        let Some(TypeDef::Optional(inner)) = tpe.as_type_def()? else {
+            panic!("expected an optional type");
+        };
+        assert_eq!(inner.data.of, item.0);
+        assert_eq!(inner.data.default_item.as_deref(), Some("\"x\""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_examples() -> Result<()> {
+        let tpe = string().example("alice")?.example("bob")?.build()?;
+        let config = tpe.as_type_def()?.unwrap().base().runtime_config.clone();
+        assert_eq!(
+            config,
+            Some(vec![(
+                "examples".to_string(),
+                r#"["alice","bob"]"#.to_string()
+            )])
+        );
+
+        let res = string()
+            .config("examples", "\"alice\"")
+            .example("bob")
+            .map(|_| ());
+        assert!(matches!(
+            res,
+            Err(e) if e.stack[0].starts_with("invalid value for config 'examples'")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_injection() -> Result<()> {
+        let secret = string()
+            .inject(Injection::Secret(InjectionData::SingleValue(SingleValue {
+                value: "API_KEY".to_string(),
+            })))
+            .build()?;
+        assert!(secret.as_type_def()?.unwrap().x_base().injection.is_some());
+
+        let plain = crate::utils::remove_injection(secret)?;
+        assert_ne!(plain, secret);
+        assert!(plain.as_type_def()?.unwrap().x_base().injection.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deprecated() -> Result<()> {
+        let model = struct_()
+            .propx("username", string().deprecated(Some("use `email`")))?
+            .propx("login", string().deprecated(None))?
+            .build()?
+            .as_struct()?;
+        let config = |key: &str| -> Result<_> {
+            let prop = model.data.get_prop(key).unwrap();
+            Ok(prop.as_type_def()?.unwrap().base().runtime_config.clone())
+        };
+        assert_eq!(
+            config("username")?,
+            Some(vec![
+                ("deprecated".to_string(), "true".to_string()),
+                (
+                    "deprecation_reason".to_string(),
+                    "\"use `email`\"".to_string()
+                )
+            ])
+        );
+        assert_eq!(
+            config("login")?,
+            Some(vec![("deprecated".to_string(), "true".to_string())])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_many() -> Result<()> {
+        let tpe = integer()
+            .config("unique", "true")
+            .config_many([("default", 1), ("max_length", 12), ("unique", 0)])
+            .build()?;
+        let config = tpe.as_type_def()?.unwrap().base().runtime_config.clone();
+        let pairs = [
+            ("unique", "true"),
+            ("default", "1"),
+            ("max_length", "12"),
+            ("unique", "0"),
+        ];
+        assert_eq!(
+            config,
+            Some(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_connect_or_create() -> Result<()> {
+        let model = crate::test_utils::models::simple_record()?;
+        let tpe = connect_or_create(model)?;
+        let Some(TypeDef::Either(inner)) = tpe.as_type_def()? else {
+            panic!("expected an either type");
+        };
+        let &[connect, create] = inner.data.variants.as_slice() else {
+            panic!("expected two variants");
+        };
+
+        let connect = TypeId(connect)
+            .as_struct()?
+            .data
+            .get_prop("connect")
+            .unwrap();
+        let keys = connect
+            .as_struct()?
+            .iter_props()
+            .map(|(k, _)| k.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["id"]);
+
+        let create = TypeId(create).as_struct()?.data.get_prop("create").unwrap();
+        let keys = create
+            .as_struct()?
+            .iter_props()
+            .map(|(k, _)| k.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["name", "age"]);
+
+        let no_id = struct_().prop("name", string().build()?).build()?;
+        assert_eq!(
+            connect_or_create(no_id),
+            Err(errors::missing_id_prop(&no_id.repr()?))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_name() -> Result<()> {
+        let named = struct_().named("User").build()?;
+        assert_eq!(named.name()?, Some("User".to_string()));
+        assert_eq!(struct_().build()?.name()?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ref_to() -> Result<()> {
+        let address = struct_()
+            .prop("city", string().build()?)
+            .named("Address")
+            .build()?;
+        let parent = struct_()
+            .propx("billing", ref_to(address)?)?
+            .propx("shipping", ref_to(address)?)?
+            .build()?
+            .as_struct()?;
+        for (_, prop_id) in parent.iter_props() {
+            assert_eq!(prop_id.resolve_ref()?.1.id(), address);
+        }
+
+        let anonymous = struct_().build()?;
+        assert_eq!(
+            ref_to(anonymous).map(|_| ()),
+            Err(errors::anonymous_type_ref(&anonymous.repr()?))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_io_inputs() -> Result<()> {
+        let model = crate::test_utils::models::simple_record()?;
+        let (create, update) = io_inputs(model)?;
+
+        let create = create.as_struct()?;
+        let keys = create.iter_props().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["name", "age"]);
+
+        let update = update.as_struct()?;
+        let keys = update.iter_props().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["name", "age", "created_at"]);
+        for (_, prop_id) in update.iter_props() {
+            assert!(matches!(prop_id.as_type_def()?, Some(TypeDef::Optional(_))));
+        }
+        let name = update.data.get_prop("name").unwrap().resolve_optional()?;
+        assert!(matches!(name.as_type_def()?, Some(TypeDef::String(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prop_union_ref() -> Result<()> {
+        union([integer().build()?, string().build()?])
+            .named("Value")
+            .build()?;
+        integer().named("Scalar").build()?;
+
+        let s = struct_()
+            .prop_union_ref("value", "Value")?
+            .prop_union_ref("scalar", "Scalar")?
+            .build()?
+            .as_struct()?;
+
+        let value = s.data.get_prop("value").unwrap();
+        assert!(matches!(value.resolve_ref()?.1, TypeDef::Union(_)));
+
+        let scalar = s.data.get_prop("scalar").unwrap();
+        assert_eq!(
+            scalar.resolve_ref().map(|_| ()),
+            Err(crate::errors::unexpected_ref_variant(
+                "Scalar", "union", "integer"
+            ))
+        );
+        Ok(())
+    }
+}
@@ -18,6 +18,16 @@ impl Default for Effect {
     }
 }
 
+/// String values accepted by the typegate format validators, some of which
+/// the core approximations in `TypeId::validate_value` would reject
+pub const FORMAT_SAMPLES: [(&str, &str); 5] = [
+    ("uuid", "123e4567-e89b-12d3-a456-426614174000"),
+    ("email", "alice@example.com"),
+    ("uri", "ftp://files.example.com/report.pdf"),
+    ("date", "2024/01/31"),
+    ("date-time", "2024-01-31T10:00:00"),
+];
+
 pub mod models {
     use std::collections::BTreeMap;
 
@@ -231,7 +241,8 @@ pub mod tree {
                     | TypeDef::Float(_)
                     | TypeDef::String(_)
                     | TypeDef::File(_)
-                    | TypeDef::Boolean(_) => Cow::Owned(vec![]),
+                    | TypeDef::Boolean(_)
+                    | TypeDef::Any(_) => Cow::Owned(vec![]),
                     TypeDef::Struct(ty) => {
                         let mut children = ty
                             .data
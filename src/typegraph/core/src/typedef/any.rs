@@ -0,0 +1,55 @@
+// Copyright Metatype OÜ, licensed under the Mozilla Public License Version 2.0.
+// SPDX-License-Identifier: MPL-2.0
+
+use common::typegraph::TypeNode;
+use errors::Result;
+
+use crate::{
+    conversion::{
+        hash::Hashable,
+        types::{BaseBuilderInit, TypeConversion},
+    },
+    errors,
+    typegraph::TypegraphContext,
+    types::{Any, TypeAny, TypeDefData},
+};
+use std::hash::Hash;
+
+impl TypeConversion for Any {
+    fn convert(&self, ctx: &mut TypegraphContext, runtime_id: Option<u32>) -> Result<TypeNode> {
+        Ok(TypeNode::Any {
+            base: BaseBuilderInit {
+                ctx,
+                base_name: "any",
+                type_id: self.id,
+                name: self.base.name.clone(),
+                runtime_idx: runtime_id.unwrap(),
+                policies: &self.extended_base.policies,
+                runtime_config: self.base.runtime_config.as_deref(),
+            }
+            .init_builder()?
+            .inject(self.extended_base.injection.clone())?
+            .build()?,
+        })
+    }
+}
+
+impl TypeDefData for TypeAny {
+    fn get_display_params_into(&self, _params: &mut Vec<String>) {}
+
+    fn variant_name(&self) -> &'static str {
+        "any"
+    }
+}
+
+impl Hashable for TypeAny {
+    fn hash(
+        &self,
+        hasher: &mut crate::conversion::hash::Hasher,
+        _tg: &mut TypegraphContext,
+        _runtime_id: Option<u32>,
+    ) -> Result<()> {
+        "any".hash(hasher);
+        Ok(())
+    }
+}
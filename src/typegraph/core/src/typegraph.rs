@@ -15,8 +15,8 @@ use crate::{
 };
 use common::typegraph::runtimes::TGRuntime;
 use common::typegraph::{
-    Materializer, ObjectTypeData, Policy, PolicyIndices, PolicyIndicesByEffect, Queries, TypeMeta,
-    TypeNode, TypeNodeBase, Typegraph,
+    AuthProtocol, Injection, Materializer, ObjectTypeData, Policy, PolicyIndices,
+    PolicyIndicesByEffect, Queries, TypeMeta, TypeNode, TypeNodeBase, Typegraph,
 };
 use indexmap::IndexMap;
 use std::cell::RefCell;
@@ -55,6 +55,7 @@ pub struct TypegraphContext {
     mapping: IdMapping,
     runtime_contexts: RuntimeContexts,
     saved_store_state: Option<SavedState>,
+    context_schema: Option<TypeId>,
 }
 
 thread_local! {
@@ -224,6 +225,8 @@ pub fn serialize(params: SerializeParams) -> Result<(String, Vec<WitArtifact>)>
         deps: Default::default(),
     };
 
+    ensure_scopes_provider(&tg)?;
+    ensure_declared_context(&tg, ctx.context_schema)?;
     tg.meta.prefix.clone_from(&params.prefix);
 
     let pretty = params.pretty;
@@ -255,6 +258,74 @@ pub fn serialize(params: SerializeParams) -> Result<(String, Vec<WitArtifact>)>
     Ok((result, artifacts))
 }
 
+/// Funcs requiring OAuth scopes need an OAuth2 provider to get them from
+fn ensure_scopes_provider(tg: &Typegraph) -> Result<()> {
+    let has_oauth2 = tg
+        .meta
+        .auths
+        .iter()
+        .any(|auth| matches!(auth.protocol, AuthProtocol::OAuth2));
+    if has_oauth2 {
+        return Ok(());
+    }
+    for type_node in tg.types.iter() {
+        if let TypeNode::Function { base, .. } = type_node {
+            if base.config.contains_key("required_scopes") {
+                return Err(errors::missing_scopes_provider(&base.title));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Declares the shape of the request context of the active typegraph: the
+/// context injections are then checked against its props on serialization.
+pub fn declare_context(schema: TypeId) -> Result<()> {
+    schema.as_struct()?;
+    with_tg_mut(|ctx| ctx.context_schema = Some(schema))
+}
+
+/// Context injections must reference a prop of the declared context, if any;
+/// only the first segment of the context path is checked.
+fn ensure_declared_context(tg: &Typegraph, schema: Option<TypeId>) -> Result<()> {
+    let Some(schema) = schema else {
+        return Ok(());
+    };
+    let schema = schema.as_struct()?;
+    for type_node in tg.types.iter() {
+        let Some(Injection::Context(data)) = &type_node.base().injection else {
+            continue;
+        };
+        for path in data.values() {
+            let field = path.split('.').next().unwrap_or(path);
+            if schema.data.get_prop(field).is_none() {
+                return Err(errors::undeclared_context_field(field));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Builds a typegraph in one call: `f` registers the runtimes and exposes the
+/// root functions within a new typegraph context, which is discarded if it fails.
+/// Test helper only; the SDKs drive the same steps through wit.
+#[cfg(test)]
+pub fn build(
+    params: TypegraphInitParams,
+    serialize_params: SerializeParams,
+    f: impl FnOnce() -> Result<()>,
+) -> Result<(String, Vec<WitArtifact>)> {
+    init(params)?;
+    if let Err(e) = f() {
+        let ctx = TG.with(|tg| tg.borrow_mut().take());
+        if let Some(state) = ctx.and_then(|ctx| ctx.saved_store_state) {
+            Store::restore(state);
+        }
+        return Err(e);
+    }
+    serialize(serialize_params)
+}
+
 fn ensure_valid_export(export_key: String, type_id: TypeId) -> Result<()> {
     match type_id.resolve_ref()?.1 {
         TypeDef::Struct(inner) => {
@@ -97,6 +97,7 @@ where
             }),
 
             TypeDef::Boolean(_)
+            | TypeDef::Any(_)
             | TypeDef::Integer(_)
             | TypeDef::Float(_)
             | TypeDef::String(_)
@@ -82,6 +82,10 @@ impl Default for TypeBase {
 #[derive(Debug, Clone, Hash)]
 pub struct TypeBoolean;
 
+/// Accepts any JSON value, including null
+#[derive(Debug, Clone, Hash)]
+pub struct TypeAny;
+
 pub type Struct = NonRefType<TypeStruct>;
 pub type Integer = NonRefType<TypeInteger>;
 pub type Float = NonRefType<TypeFloat>;
@@ -93,6 +97,7 @@ pub type List = NonRefType<TypeList>;
 pub type Optional = NonRefType<TypeOptional>;
 pub type Union = NonRefType<TypeUnion>;
 pub type Either = NonRefType<TypeEither>;
+pub type Any = NonRefType<TypeAny>;
 
 #[derive(Debug, Clone)]
 #[enum_dispatch(TypeDefExt, TypeConversion)]
@@ -108,6 +113,7 @@ pub enum TypeDef {
     Optional(Rc<Optional>),
     Union(Rc<Union>),
     Either(Rc<Either>),
+    Any(Rc<Any>),
 }
 
 impl TypeDef {
@@ -70,6 +70,25 @@ impl TypeId {
         }
     }
 
+    /// Whether a policy chain is attached to the type, after resolving references
+    #[allow(dead_code)]
+    pub fn has_policy(&self) -> Result<bool> {
+        Ok(!self.resolve_ref()?.1.x_base().policies.is_empty())
+    }
+
+    /// Structural equality, unlike `==` which compares the ids.
+    /// Only the type data are compared: nested types (items, props, variants)
+    /// must have the same ids, and the base (name, config, policies) is ignored.
+    #[allow(dead_code)]
+    pub fn structurally_eq(&self, other: &TypeId) -> Result<bool> {
+        let params = |type_def: &TypeDef| {
+            let mut params = vec![];
+            type_def.data().get_display_params_into(&mut params);
+            (type_def.variant_name(), params)
+        };
+        Ok(params(&self.resolve_ref()?.1) == params(&other.resolve_ref()?.1))
+    }
+
     pub fn hash_child_type(
         &self,
         state: &mut crate::conversion::hash::Hasher,
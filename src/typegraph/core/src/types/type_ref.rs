@@ -5,7 +5,11 @@ use std::{collections::HashMap, rc::Rc};
 
 use crate::errors::{self, ErrorContext, Result};
 use crate::global_store::Store;
-use crate::types::{Type, TypeDef, TypeId};
+use crate::types::{Type, TypeDef, TypeDefExt, TypeId};
+
+/// Ref attribute asserting the variant (e.g. `union`) of the target type;
+/// checked when the ref is resolved.
+pub const EXPECTED_VARIANT_ATTR: &str = "expected_variant";
 
 #[derive(Clone, Debug)]
 pub struct TypeRef {
@@ -38,11 +42,23 @@ impl TypeRef {
 
     pub fn try_resolve(&self) -> Result<TypeDef> {
         match self.resolve()? {
-            Some(t) => Ok(t),
+            Some(t) => {
+                self.check_expected_variant(&t)?;
+                Ok(t)
+            }
             None => Err(errors::unregistered_type_name(&self.name)),
         }
     }
 
+    fn check_expected_variant(&self, type_def: &TypeDef) -> Result<()> {
+        match self.attributes.get(EXPECTED_VARIANT_ATTR) {
+            Some(expected) if expected != type_def.variant_name() => Err(
+                errors::unexpected_ref_variant(&self.name, expected, type_def.variant_name()),
+            ),
+            _ => Ok(()),
+        }
+    }
+
     pub fn resolve_ref(&self) -> Result<(RefData, TypeDef)> {
         let ref_data = RefData {
             id: self.id,
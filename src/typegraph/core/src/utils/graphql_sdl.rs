@@ -0,0 +1,222 @@
+// Copyright Metatype OÜ, licensed under the Mozilla Public License Version 2.0.
+// SPDX-License-Identifier: MPL-2.0
+
+//! GraphQL SDL export of the functions exposed by a serialized typegraph.
+//!
+//! Read functions are fields of `Query`, the others of `Mutation`. Objects
+//! are `type`s named after their title, or `input`s with an `Input` suffix
+//! when used as arguments. Unions of objects are `union`s; other unions, and
+//! unions in inputs, have no GraphQL equivalent and are exported as a `JSON`
+//! scalar, as are `any` types. Namespaces are not supported yet and skipped.
+//! Injected props are not part of the exposed inputs, as in the typegate.
+
+use std::collections::BTreeSet;
+
+use common::typegraph::{EffectType, TypeNode, Typegraph};
+use indexmap::IndexMap;
+
+use crate::errors::{self, Result};
+
+pub fn to_graphql_sdl(tg: &Typegraph) -> Result<String> {
+    let mut builder = SdlBuilder {
+        tg,
+        definitions: IndexMap::new(),
+        scalars: BTreeSet::new(),
+    };
+
+    let TypeNode::Object { data, .. } = builder.node(0)? else {
+        return Err(errors::invalid_type("object", "root"));
+    };
+    let mut queries = vec![];
+    let mut mutations = vec![];
+    for (name, idx) in data.properties.iter() {
+        let TypeNode::Function { data, .. } = builder.node(*idx)? else {
+            continue;
+        };
+        let mat = tg
+            .materializers
+            .get(data.materializer as usize)
+            .ok_or_else(|| errors::object_not_found("materializer", data.materializer))?;
+        let field = builder.function_field(name, data.input, data.output)?;
+        match mat.effect.effect {
+            Some(EffectType::Read) | None => queries.push(field),
+            Some(_) => mutations.push(field),
+        }
+    }
+
+    let mut blocks = vec![];
+    if !queries.is_empty() {
+        blocks.push(block("type", "Query", &queries));
+    }
+    if !mutations.is_empty() {
+        blocks.push(block("type", "Mutation", &mutations));
+    }
+    blocks.extend(builder.scalars.iter().map(|s| format!("scalar {s}")));
+    blocks.extend(builder.definitions.into_values());
+    Ok(blocks.join("\n\n") + "\n")
+}
+
+struct SdlBuilder<'a> {
+    tg: &'a Typegraph,
+    definitions: IndexMap<String, String>,
+    scalars: BTreeSet<String>,
+}
+
+impl<'a> SdlBuilder<'a> {
+    fn node(&self, idx: u32) -> Result<&'a TypeNode> {
+        self.tg
+            .types
+            .get(idx as usize)
+            .ok_or_else(|| errors::object_not_found("type", idx))
+    }
+
+    fn is_injected(&self, idx: u32) -> bool {
+        self.node(idx)
+            .map_or(false, |node| node.base().injection.is_some())
+    }
+
+    fn function_field(&mut self, name: &str, input: u32, output: u32) -> Result<String> {
+        let TypeNode::Object { data, .. } = self.node(input)? else {
+            return Err(errors::invalid_type(
+                "object",
+                self.node(input)?.type_name(),
+            ));
+        };
+        let args = data
+            .properties
+            .iter()
+            .filter(|(_, idx)| !self.is_injected(**idx))
+            .map(|(arg, idx)| -> Result<_> { Ok(format!("{arg}: {}", self.type_ref(*idx, true)?)) })
+            .collect::<Result<Vec<_>>>()?;
+        let output = self.type_ref(output, false)?;
+        if args.is_empty() {
+            Ok(format!("{name}: {output}"))
+        } else {
+            Ok(format!("{name}({}): {output}", args.join(", ")))
+        }
+    }
+
+    /// Non-null reference to the GraphQL type of the node
+    fn type_ref(&mut self, idx: u32, input: bool) -> Result<String> {
+        let node = self.node(idx)?;
+        let base = node.base();
+        let name = match node {
+            TypeNode::Optional { data, .. } => {
+                let item = self.type_ref(data.item, input)?;
+                return Ok(item.trim_end_matches('!').to_string());
+            }
+            TypeNode::List { data, .. } => format!("[{}]", self.type_ref(data.items, input)?),
+            _ if base.as_id => "ID".to_string(),
+            TypeNode::Boolean { .. } => "Boolean".to_string(),
+            TypeNode::Integer { .. } => "Int".to_string(),
+            TypeNode::Float { .. } => "Float".to_string(),
+            TypeNode::String { .. } => self.enum_(node)?.unwrap_or_else(|| "String".to_string()),
+            TypeNode::File { .. } => self.scalar("File"),
+            TypeNode::Any { .. } => self.scalar("JSON"),
+            TypeNode::Object { data, .. } => {
+                let name = match input {
+                    true => format!("{}Input", base.title),
+                    false => base.title.clone(),
+                };
+                if !self.definitions.contains_key(&name) {
+                    // placeholder for recursive types
+                    self.definitions.insert(name.clone(), String::new());
+                    let mut fields = vec![];
+                    for (prop, idx) in data.properties.iter() {
+                        match self.node(*idx)? {
+                            _ if input && self.is_injected(*idx) => {}
+                            TypeNode::Function { data, .. } if !input => {
+                                fields.push(self.function_field(prop, data.input, data.output)?)
+                            }
+                            _ => fields.push(format!("{prop}: {}", self.type_ref(*idx, input)?)),
+                        }
+                    }
+                    let keyword = if input { "input" } else { "type" };
+                    self.definitions
+                        .insert(name.clone(), block(keyword, &name, &fields));
+                }
+                name
+            }
+            TypeNode::Union { data, .. } => self.union(node, &data.any_of, input)?,
+            TypeNode::Either { data, .. } => self.union(node, &data.one_of, input)?,
+            TypeNode::Function { .. } => {
+                return Err(errors::invalid_type("a data type", node.type_name()));
+            }
+        };
+        Ok(format!("{name}!"))
+    }
+
+    fn union(&mut self, node: &TypeNode, variants: &[u32], input: bool) -> Result<String> {
+        let all_objects = variants
+            .iter()
+            .map(|idx| -> Result<_> { Ok(matches!(self.node(*idx)?, TypeNode::Object { .. })) })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .all(|is_object| is_object);
+        if input || !all_objects {
+            return Ok(self.scalar("JSON"));
+        }
+
+        let name = node.base().title.clone();
+        if !self.definitions.contains_key(&name) {
+            self.definitions.insert(name.clone(), String::new());
+            let members = variants
+                .iter()
+                .map(|idx| -> Result<_> {
+                    Ok(self
+                        .type_ref(*idx, false)?
+                        .trim_end_matches('!')
+                        .to_string())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            self.definitions.insert(
+                name.clone(),
+                format!("union {name} = {}", members.join(" | ")),
+            );
+        }
+        Ok(name)
+    }
+
+    /// Enum for a string enumeration whose values are all valid GraphQL names
+    fn enum_(&mut self, node: &TypeNode) -> Result<Option<String>> {
+        let base = node.base();
+        let Some(enumeration) = &base.enumeration else {
+            return Ok(None);
+        };
+        let values = enumeration
+            .iter()
+            .map(|v| {
+                serde_json::from_str::<String>(v).map_err(|e| errors::TgError::from(e.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !values.iter().all(|v| is_valid_name(v)) {
+            return Ok(None);
+        }
+        let name = base.title.clone();
+        self.definitions
+            .entry(name.clone())
+            .or_insert_with(|| block("enum", &name, &values));
+        Ok(Some(name))
+    }
+
+    fn scalar(&mut self, name: &str) -> String {
+        self.scalars.insert(name.to_string());
+        name.to_string()
+    }
+}
+
+fn block(keyword: &str, name: &str, lines: &[String]) -> String {
+    let lines = lines
+        .iter()
+        .map(|line| format!("  {line}\n"))
+        .collect::<String>();
+    format!("{keyword} {name} {{\n{lines}}}")
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map_or(false, |c| c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
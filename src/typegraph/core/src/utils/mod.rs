@@ -23,11 +23,14 @@ use std::path::Path;
 mod archive;
 mod artifacts;
 mod fs;
+pub mod graphql_sdl;
 pub mod metagen_utils;
 mod oauth2;
+pub mod openapi;
 mod pathlib;
 pub mod postprocess;
 pub mod reduce;
+pub mod secrets;
 
 fn find_missing_props(
     supertype_id: TypeId,
@@ -264,6 +267,16 @@ impl crate::wit::utils::Guest for crate::Lib {
         remove_injections_recursive(id.into()).map(|id| id.into())
     }
 
+    fn to_openapi(tg_json: String) -> Result<String> {
+        let tg = serde_json::from_str(&tg_json).map_err(|e| e.to_string())?;
+        openapi::to_openapi(&tg)
+    }
+
+    fn to_graphql_sdl(tg_json: String) -> Result<String> {
+        let tg = serde_json::from_str(&tg_json).map_err(|e| e.to_string())?;
+        graphql_sdl::to_graphql_sdl(&tg)
+    }
+
     fn metagen_exec(config: MdkConfig) -> Result<Vec<MdkOutput>, String> {
         let gen_config: metagen::Config = serde_json::from_str(&config.config_json)
             .map_err(|e| format!("Load metagen config: {}", e))?;
@@ -501,3 +501,37 @@ pub fn named_provider(name: &str) -> Result<Box<dyn StdOauth2Provider>> {
         _ => Err(format!("Unknown provider: {}", name).into()),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::typegraph::{Auth, AuthProtocol};
+
+    #[test]
+    fn test_github_provider() -> Result<()> {
+        let auth = Oauth2Builder::new("openid email".to_string())
+            .no_profiler()
+            .build(named_provider("github")?)?;
+        let auth: Auth = serde_json::from_str(&auth).unwrap();
+
+        assert_eq!(auth.name, "github");
+        assert!(matches!(auth.protocol, AuthProtocol::OAuth2));
+        assert_eq!(
+            auth.auth_data["authorize_url"],
+            "https://github.com/login/oauth/authorize"
+        );
+        assert_eq!(
+            auth.auth_data["access_url"],
+            "https://github.com/login/oauth/access_token"
+        );
+        assert_eq!(auth.auth_data["profile_url"], "https://api.github.com/user");
+        assert_eq!(auth.auth_data["scopes"], "openid email");
+        assert!(auth.auth_data["profiler"].is_null());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_provider() {
+        assert!(named_provider("myspace").is_err());
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright Metatype OÜ, licensed under the Mozilla Public License Version 2.0.
+// SPDX-License-Identifier: MPL-2.0
+
+//! OpenAPI 3.1 export of the functions exposed by a serialized typegraph.
+//!
+//! Each exposed function maps to a `/{name}` path, nested under the names of
+//! its namespaces: read functions are `GET` operations taking their input as
+//! query parameters, the others are `POST` operations taking a JSON body.
+//! Objects are described in `components/schemas`, under their title.
+//! Injected props are left out, as clients cannot set them.
+//!
+//! Not supported: file types are described as binary strings, and functions
+//! nested in output objects (resolvers) are left out of the schemas.
+
+use common::typegraph::{EffectType, TypeNode, Typegraph};
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use crate::errors::{self, Result};
+
+pub fn to_openapi(tg: &Typegraph) -> Result<String> {
+    let mut builder = SchemaBuilder {
+        tg,
+        components: IndexMap::new(),
+    };
+    let mut paths = IndexMap::new();
+    builder.collect_paths(0, "", &mut paths)?;
+
+    let title = tg
+        .name()
+        .map_err(|e| errors::TgError::from(e.to_string()))?;
+    let document = json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": title,
+            "version": tg.meta.version,
+        },
+        "paths": paths,
+        "components": {
+            "schemas": builder.components,
+        },
+    });
+    serde_json::to_string_pretty(&document).map_err(|e| e.to_string().into())
+}
+
+struct SchemaBuilder<'a> {
+    tg: &'a Typegraph,
+    components: IndexMap<String, Value>,
+}
+
+impl<'a> SchemaBuilder<'a> {
+    fn node(&self, idx: u32) -> Result<&'a TypeNode> {
+        self.tg
+            .types
+            .get(idx as usize)
+            .ok_or_else(|| errors::object_not_found("type", idx))
+    }
+
+    fn is_injected(&self, idx: u32) -> bool {
+        self.node(idx)
+            .map_or(false, |node| node.base().injection.is_some())
+    }
+
+    fn collect_paths(
+        &mut self,
+        namespace_idx: u32,
+        prefix: &str,
+        paths: &mut IndexMap<String, Value>,
+    ) -> Result<()> {
+        let node = self.node(namespace_idx)?;
+        let TypeNode::Object { data, .. } = node else {
+            return Err(errors::invalid_type("object", node.type_name()));
+        };
+        for (name, idx) in data.properties.iter() {
+            let path = format!("{prefix}/{name}");
+            match self.node(*idx)? {
+                TypeNode::Function { data, .. } => {
+                    let mat = self
+                        .tg
+                        .materializers
+                        .get(data.materializer as usize)
+                        .ok_or_else(|| {
+                            errors::object_not_found("materializer", data.materializer)
+                        })?;
+                    let response = json!({
+                        "200": {
+                            "description": "Successful response",
+                            "content": {
+                                "application/json": { "schema": self.schema(data.output)? },
+                            },
+                        },
+                    });
+                    let operation = match mat.effect.effect {
+                        Some(EffectType::Read) | None => json!({
+                            "get": {
+                                "operationId": name,
+                                "parameters": self.query_parameters(data.input)?,
+                                "responses": response,
+                            },
+                        }),
+                        Some(_) => json!({
+                            "post": {
+                                "operationId": name,
+                                "requestBody": {
+                                    "required": true,
+                                    "content": {
+                                        "application/json": { "schema": self.schema(data.input)? },
+                                    },
+                                },
+                                "responses": response,
+                            },
+                        }),
+                    };
+                    paths.insert(path, operation);
+                }
+                TypeNode::Object { .. } => self.collect_paths(*idx, &path, paths)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn query_parameters(&mut self, input_idx: u32) -> Result<Vec<Value>> {
+        let node = self.node(input_idx)?;
+        let TypeNode::Object { data, .. } = node else {
+            return Err(errors::invalid_type("object", node.type_name()));
+        };
+        data.properties
+            .iter()
+            .filter(|(_, idx)| !self.is_injected(**idx))
+            .map(|(name, idx)| -> Result<Value> {
+                let (item, required) = match self.node(*idx)? {
+                    TypeNode::Optional { data, .. } => (data.item, false),
+                    _ => (*idx, true),
+                };
+                Ok(json!({
+                    "name": name,
+                    "in": "query",
+                    "required": required,
+                    "schema": self.schema(item)?,
+                }))
+            })
+            .collect()
+    }
+
+    fn schema(&mut self, idx: u32) -> Result<Value> {
+        let node = self.node(idx)?;
+        let base = node.base();
+        let mut schema = match node {
+            TypeNode::Optional { data, .. } => {
+                json!({ "anyOf": [self.schema(data.item)?, { "type": "null" }] })
+            }
+            TypeNode::Boolean { .. } => json!({ "type": "boolean" }),
+            TypeNode::Integer { data, .. } => with_type("integer", data)?,
+            TypeNode::Float { data, .. } => with_type("number", data)?,
+            TypeNode::String { data, .. } => with_type("string", data)?,
+            TypeNode::File { .. } => json!({ "type": "string", "format": "binary" }),
+            TypeNode::List { data, .. } => {
+                let mut schema = with_type("array", data)?;
+                schema["items"] = self.schema(data.items)?;
+                schema
+            }
+            TypeNode::Object { data, .. } => {
+                let reference = json!({
+                    "$ref": format!("#/components/schemas/{}", base.title),
+                });
+                if self.components.contains_key(&base.title) {
+                    return Ok(reference);
+                }
+                // placeholder for recursive types
+                self.components.insert(base.title.clone(), Value::Null);
+
+                let mut properties = serde_json::Map::new();
+                let mut required = vec![];
+                for (name, idx) in data.properties.iter() {
+                    match self.node(*idx)? {
+                        _ if self.is_injected(*idx) => continue,
+                        TypeNode::Function { .. } => continue,
+                        TypeNode::Optional { data, .. } => {
+                            properties.insert(name.clone(), self.schema(data.item)?);
+                        }
+                        _ => {
+                            properties.insert(name.clone(), self.schema(*idx)?);
+                            required.push(name.clone());
+                        }
+                    }
+                }
+                let mut schema = json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                });
+                annotate(&mut schema, node)?;
+                self.components.insert(base.title.clone(), schema);
+                return Ok(reference);
+            }
+            TypeNode::Union { data, .. } => json!({ "anyOf": self.schemas(&data.any_of)? }),
+            TypeNode::Either { data, .. } => json!({ "oneOf": self.schemas(&data.one_of)? }),
+            TypeNode::Any { .. } => json!({}),
+            TypeNode::Function { .. } => {
+                return Err(errors::invalid_type("a data type", node.type_name()));
+            }
+        };
+        annotate(&mut schema, node)?;
+        Ok(schema)
+    }
+
+    fn schemas(&mut self, indices: &[u32]) -> Result<Vec<Value>> {
+        indices.iter().map(|idx| self.schema(*idx)).collect()
+    }
+}
+
+/// Schema of the given type, with the constraints of the type data; these
+/// are serialized with the JSON Schema keywords already.
+fn with_type(type_: &str, data: &impl serde::Serialize) -> Result<Value> {
+    let mut schema =
+        serde_json::to_value(data).map_err(|e| errors::TgError::from(e.to_string()))?;
+    schema["type"] = json!(type_);
+    Ok(schema)
+}
+
+fn annotate(schema: &mut Value, node: &TypeNode) -> Result<()> {
+    let base = node.base();
+    if let Some(description) = &base.description {
+        schema["description"] = json!(description);
+    }
+    if let Some(enumeration) = &base.enumeration {
+        let values = enumeration
+            .iter()
+            .map(|v| serde_json::from_str(v).map_err(|e| errors::TgError::from(e.to_string())))
+            .collect::<Result<Vec<Value>>>()?;
+        schema["enum"] = json!(values);
+    }
+    Ok(())
+}
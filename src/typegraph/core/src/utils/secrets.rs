@@ -0,0 +1,80 @@
+// Copyright Metatype OÜ, licensed under the Mozilla Public License Version 2.0.
+// SPDX-License-Identifier: MPL-2.0
+
+//! Secrets referenced by a serialized typegraph, to check the deployment
+//! environment before deploying: secret injections, secret args of the
+//! runtimes, and secrets passed to the materializers (deno, python).
+
+use std::collections::BTreeSet;
+
+use common::typegraph::runtimes::{KnownRuntime, TGRuntime};
+use common::typegraph::{Injection, Typegraph};
+
+use crate::errors::{self, Result};
+
+/// Names of the secrets referenced by the typegraph, sorted and deduplicated;
+/// the mandatory secrets of the typegraph meta are included.
+pub fn required_secrets(tg: &Typegraph) -> Vec<String> {
+    let mut secrets = BTreeSet::new();
+    secrets.extend(tg.meta.secrets.iter().cloned());
+
+    for node in tg.types.iter() {
+        if let Some(Injection::Secret(data)) = &node.base().injection {
+            secrets.extend(data.values().into_iter().cloned());
+        }
+    }
+
+    for runtime in tg.runtimes.iter() {
+        let TGRuntime::Known(runtime) = runtime else {
+            continue;
+        };
+        match runtime {
+            KnownRuntime::HTTP(data) => {
+                secrets.extend(data.cert_secret.iter().cloned());
+                secrets.extend(data.basic_auth_secret.iter().cloned());
+            }
+            KnownRuntime::Prisma(data) => {
+                secrets.insert(data.connection_string_secret.clone());
+            }
+            KnownRuntime::S3(data) => secrets.extend([
+                data.host_secret.clone(),
+                data.region_secret.clone(),
+                data.access_key_secret.clone(),
+                data.secret_key_secret.clone(),
+                data.path_style_secret.clone(),
+            ]),
+            KnownRuntime::Temporal(data) => {
+                secrets.insert(data.host_secret.clone());
+                secrets.extend(data.namespace_secret.iter().cloned());
+            }
+            KnownRuntime::Substantial(data) => {
+                secrets.extend(data.basic_auth_secret.iter().cloned());
+            }
+            _ => {}
+        }
+    }
+
+    for mat in tg.materializers.iter() {
+        let names = mat.data.get("secrets").and_then(|s| s.as_array());
+        secrets.extend(
+            names
+                .into_iter()
+                .flatten()
+                .filter_map(|name| name.as_str().map(|n| n.to_string())),
+        );
+    }
+
+    secrets.into_iter().collect()
+}
+
+/// Strict check: fails on the first secret referenced by the typegraph
+/// that is not in `declared`.
+pub fn ensure_declared_secrets(tg: &Typegraph, declared: &[String]) -> Result<()> {
+    match required_secrets(tg)
+        .into_iter()
+        .find(|name| !declared.contains(name))
+    {
+        Some(name) => Err(errors::undeclared_secret(&name)),
+        None => Ok(()),
+    }
+}
@@ -21,3 +21,7 @@ pub fn invalid_output_type_predefined(name: &str, expected: &str, got: &str) ->
 pub fn unknown_predefined_function(name: &str) -> Error {
     format!("unknown predefined function {}", name).into()
 }
+
+pub fn unknown_path_param(param: &str, path: &str) -> Error {
+    format!("path parameter '{param}' of {path:?} is not a prop of the input type").into()
+}
@@ -9,6 +9,10 @@ use crate::{global_store::Store, runtimes::Materializer};
 
 use super::errors;
 use super::types::utils as type_utils;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static PATH_PARAM_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{(\w+)\}").unwrap());
 
 impl Materializer {
     pub fn validate(&self, func: &TypeFunc) -> Result<()> {
@@ -17,12 +21,31 @@ impl Materializer {
             (Runtime::Deno, MaterializerData::Deno(mat_data)) => {
                 Self::validate_deno_mat(mat_data, func)
             }
+            (Runtime::Http(_), MaterializerData::Http(_)) => self.validate_path_params(func),
             // TODO
             // _ => Err(errors::invalid_runtime_type("", "")),
             _ => Ok(()), // TODO validate path components??
         }
     }
 
+    /// The typegate substitutes each `{param}` segment of an HTTP request path
+    /// with the input prop of the same name: it must exist.
+    /// `:param` segments are not checked, as they can also be verbs like in
+    /// `/items:batchGet`.
+    pub fn validate_path_params(&self, func: &TypeFunc) -> Result<()> {
+        let MaterializerData::Http(mat_data) = &self.data else {
+            return Ok(());
+        };
+        let input = TypeId(func.inp).as_struct()?;
+        for param in PATH_PARAM_REGEX.captures_iter(&mat_data.path) {
+            let param = &param[1];
+            if input.data.get_prop(param).is_none() {
+                return Err(errors::unknown_path_param(param, &mat_data.path));
+            }
+        }
+        Ok(())
+    }
+
     fn validate_deno_mat(mat_data: &DenoMaterializer, func: &TypeFunc) -> Result<()> {
         match mat_data {
             DenoMaterializer::Predefined(predef) => {
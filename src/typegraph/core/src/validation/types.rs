@@ -3,8 +3,22 @@
 
 use crate::global_store::Store;
 use crate::types::{Type, TypeDef, TypeDefExt, TypeId};
-use crate::wit::core::TypeFunc;
+use crate::wit::core::{PolicySpec, TypeFloat, TypeFunc, TypeInteger, TypeString};
 use crate::{errors, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static UUID_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[0-9a-fA-F]{8}(-[0-9a-fA-F]{4}){3}-[0-9a-fA-F]{12}$").unwrap());
+static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap());
+static URI_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*:\S+$").unwrap());
+static DATE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap());
+static DATE_TIME_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})$").unwrap()
+});
+
+/// Tolerance for the float `multiple_of` check, relative to the quotient
+const MULTIPLE_OF_EPSILON: f64 = 1e-9;
 
 impl TypeFunc {
     pub fn validate(&self) -> Result<()> {
@@ -21,10 +35,66 @@ impl TypeFunc {
     }
 }
 
+pub fn ensure_registered_policies(policy_chain: &[PolicySpec]) -> Result<()> {
+    for spec in policy_chain {
+        let ids = match spec {
+            PolicySpec::Simple(id) => vec![*id],
+            PolicySpec::PerEffect(p) => [p.read, p.create, p.update, p.delete]
+                .into_iter()
+                .flatten()
+                .collect(),
+        };
+        for id in ids {
+            Store::get_policy(id).map_err(|_| errors::unregistered_policy(id))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn ensure_unique_enum_values<T: PartialEq + std::fmt::Display>(values: &[T]) -> Result<()> {
+    for (i, value) in values.iter().enumerate() {
+        if values[..i].contains(value) {
+            return Err(errors::duplicate_enum_value(&value.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Enumerated values must satisfy the other constraints of the type, checked
+/// by `check`; the error lists all the offending values.
+pub fn ensure_consistent_enumeration(
+    values: impl IntoIterator<Item = serde_json::Value>,
+    check: impl Fn(&serde_json::Value) -> Result<()>,
+) -> Result<()> {
+    let offending = values
+        .into_iter()
+        .filter(|value| check(value).is_err())
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>();
+    match offending.is_empty() {
+        true => Ok(()),
+        false => Err(errors::inconsistent_enum_values(&offending)),
+    }
+}
+
+/// Checks the shape of a value against a type, e.g. for static values and
+/// defaults; the constraints (formats, patterns, bounds...) are left to the
+/// typegate, whose validators the core does not replicate exactly.
 pub fn validate_value(value: &serde_json::Value, type_id: TypeId, path: String) -> Result<()> {
+    validate(value, type_id, path, false)
+}
+
+fn validate(
+    value: &serde_json::Value,
+    type_id: TypeId,
+    path: String,
+    constraints: bool,
+) -> Result<()> {
     match TypeDef::try_from(type_id)? {
         TypeDef::Func(_) => Err("cannot validate function".into()),
 
+        TypeDef::Any(_) => Ok(()),
+
         TypeDef::Struct(inner) => {
             let Some(value) = value.as_object() else {
                 return Err(format!(
@@ -34,11 +104,20 @@ pub fn validate_value(value: &serde_json::Value, type_id: TypeId, path: String)
                 .into());
             };
             for (key, type_id) in inner.iter_props() {
-                validate_value(
-                    value.get(key).unwrap_or(&serde_json::Value::Null),
-                    type_id,
-                    format!("{path}.{key}"),
-                )?;
+                let prop_value = match value.get(key) {
+                    Some(prop_value) => prop_value,
+                    None if !constraints
+                        || matches!(type_id.resolve_ref()?.1, TypeDef::Optional(_)) =>
+                    {
+                        &serde_json::Value::Null
+                    }
+                    None => return Err(format!("missing required prop {key:?} at {path:?}").into()),
+                };
+                validate(prop_value, type_id, format!("{path}.{key}"), constraints)?;
+            }
+            if constraints {
+                let object = serde_json::Value::Object(value.clone());
+                check_enumeration(&object, inner.data.enumeration.as_deref(), &path)?;
             }
             // TODO min max?
             Ok(())
@@ -53,9 +132,37 @@ pub fn validate_value(value: &serde_json::Value, type_id: TypeId, path: String)
                 .into());
             };
             for (i, value) in value.iter().enumerate() {
-                validate_value(value, inner.data.of.into(), format!("{path}[{i}]"))?;
+                validate(
+                    value,
+                    inner.data.of.into(),
+                    format!("{path}[{i}]"),
+                    constraints,
+                )?;
+            }
+            if !constraints {
+                return Ok(());
+            }
+            let len = value.len() as u32;
+            let array = serde_json::Value::Array(value.clone());
+            check(
+                &array,
+                &path,
+                "min items",
+                inner.data.min.map_or(true, |min| len >= min),
+            )?;
+            check(
+                &array,
+                &path,
+                "max items",
+                inner.data.max.map_or(true, |max| len <= max),
+            )?;
+            if inner.data.unique_items == Some(true) {
+                let unique = value
+                    .iter()
+                    .enumerate()
+                    .all(|(i, item)| !value[..i].contains(item));
+                check(&array, &path, "unique items", unique)?;
             }
-            // TODO min max?
             Ok(())
         }
 
@@ -63,14 +170,14 @@ pub fn validate_value(value: &serde_json::Value, type_id: TypeId, path: String)
             if value.is_null() {
                 return Ok(());
             }
-            validate_value(value, inner.data.of.into(), path)?;
+            validate(value, inner.data.of.into(), path, constraints)?;
             Ok(())
         }
 
         TypeDef::Either(inner) => {
             let mut match_count = 0;
             for type_id in inner.data.variants.iter() {
-                match validate_value(value, type_id.into(), path.clone()) {
+                match validate(value, type_id.into(), path.clone(), constraints) {
                     Ok(()) => match_count += 1,
                     Err(_) => continue,
                 }
@@ -92,7 +199,7 @@ pub fn validate_value(value: &serde_json::Value, type_id: TypeId, path: String)
 
         TypeDef::Union(inner) => {
             for type_id in inner.data.variants.iter() {
-                match validate_value(value, type_id.into(), path.clone()) {
+                match validate(value, type_id.into(), path.clone(), constraints) {
                     Ok(()) => return Ok(()),
                     Err(_) => continue,
                 }
@@ -104,41 +211,14 @@ pub fn validate_value(value: &serde_json::Value, type_id: TypeId, path: String)
             .into())
         }
 
-        TypeDef::String(_inner) => {
-            let Some(_value) = value.as_str() else {
-                return Err(format!(
-                    "expected string at {path:?}, got: {}",
-                    serde_json::to_string(&value).map_err(|e| e.to_string())?,
-                )
-                .into());
-            };
-            // TODO min max
-            Ok(())
-        }
+        TypeDef::String(inner) if constraints => check_string(value, &inner.data, &path),
+        TypeDef::String(_) => check_string(value, &Default::default(), &path),
 
-        TypeDef::Integer(_inner) => {
-            let Some(_value) = value.as_i64() else {
-                return Err(format!(
-                    "expected integer at {path:?}, got: {}",
-                    serde_json::to_string(&value).map_err(|e| e.to_string())?,
-                )
-                .into());
-            };
-            // TODO min max
-            Ok(())
-        }
+        TypeDef::Integer(inner) if constraints => check_integer(value, &inner.data, &path),
+        TypeDef::Integer(_) => check_integer(value, &Default::default(), &path),
 
-        TypeDef::Float(_inner) => {
-            let Some(_value) = value.as_f64() else {
-                return Err(format!(
-                    "expected float at {path:?}, got: {}",
-                    serde_json::to_string(&value).map_err(|e| e.to_string())?,
-                )
-                .into());
-            };
-            // TODO min max
-            Ok(())
-        }
+        TypeDef::Float(inner) if constraints => check_float(value, &inner.data, &path),
+        TypeDef::Float(_) => check_float(value, &Default::default(), &path),
 
         TypeDef::Boolean(_inner) => {
             let Some(_) = value.as_bool() else {
@@ -155,6 +235,184 @@ pub fn validate_value(value: &serde_json::Value, type_id: TypeId, path: String)
     }
 }
 
+pub fn check_integer(value: &serde_json::Value, data: &TypeInteger, path: &str) -> Result<()> {
+    let Some(n) = value.as_i64() else {
+        return Err(format!(
+            "expected integer at {path:?}, got: {}",
+            serde_json::to_string(&value).map_err(|e| e.to_string())?,
+        )
+        .into());
+    };
+    let bound = |b: Option<i32>, f: fn(i64, i64) -> bool| b.map_or(true, |b| f(n, b.into()));
+    check(value, path, "minimum", bound(data.min, |n, b| n >= b))?;
+    check(value, path, "maximum", bound(data.max, |n, b| n <= b))?;
+    check(
+        value,
+        path,
+        "exclusive minimum",
+        bound(data.exclusive_minimum, |n, b| n > b),
+    )?;
+    check(
+        value,
+        path,
+        "exclusive maximum",
+        bound(data.exclusive_maximum, |n, b| n < b),
+    )?;
+    check(
+        value,
+        path,
+        "multiple of",
+        bound(data.multiple_of, |n, b| n.checked_rem(b) == Some(0)),
+    )?;
+    if let Some(enumeration) = &data.enumeration {
+        let found = enumeration.iter().any(|v| i64::from(*v) == n);
+        check(value, path, "enumeration", found)?;
+    }
+    Ok(())
+}
+
+pub fn check_float(value: &serde_json::Value, data: &TypeFloat, path: &str) -> Result<()> {
+    let Some(n) = value.as_f64() else {
+        return Err(format!(
+            "expected float at {path:?}, got: {}",
+            serde_json::to_string(&value).map_err(|e| e.to_string())?,
+        )
+        .into());
+    };
+    let bound = |b: Option<f64>, f: fn(f64, f64) -> bool| b.map_or(true, |b| f(n, b));
+    check(value, path, "minimum", bound(data.min, |n, b| n >= b))?;
+    check(value, path, "maximum", bound(data.max, |n, b| n <= b))?;
+    check(
+        value,
+        path,
+        "exclusive minimum",
+        bound(data.exclusive_minimum, |n, b| n > b),
+    )?;
+    check(
+        value,
+        path,
+        "exclusive maximum",
+        bound(data.exclusive_maximum, |n, b| n < b),
+    )?;
+    check(
+        value,
+        path,
+        "multiple of",
+        bound(data.multiple_of, |n, b| {
+            let q = n / b;
+            (q - q.round()).abs() <= MULTIPLE_OF_EPSILON * q.abs().max(1.0)
+        }),
+    )?;
+    if let Some(enumeration) = &data.enumeration {
+        check(value, path, "enumeration", enumeration.contains(&n))?;
+    }
+    Ok(())
+}
+
+pub fn check_string(value: &serde_json::Value, data: &TypeString, path: &str) -> Result<()> {
+    check_string_with_pattern(value, data, string_pattern(data)?.as_ref(), path)
+}
+
+/// Compiles the pattern constraint of a string type, if any
+pub fn string_pattern(data: &TypeString) -> Result<Option<Regex>> {
+    data.pattern
+        .as_deref()
+        .map(|pattern| Regex::new(pattern).map_err(|e| e.to_string().into()))
+        .transpose()
+}
+
+/// Same as `check_string`, with the pattern compiled by the caller
+/// (see `string_pattern`) to check several values against the same type
+pub fn check_string_with_pattern(
+    value: &serde_json::Value,
+    data: &TypeString,
+    pattern: Option<&Regex>,
+    path: &str,
+) -> Result<()> {
+    let Some(s) = value.as_str() else {
+        return Err(format!(
+            "expected string at {path:?}, got: {}",
+            serde_json::to_string(&value).map_err(|e| e.to_string())?,
+        )
+        .into());
+    };
+    let len = s.chars().count() as u32;
+    check(
+        value,
+        path,
+        "min length",
+        data.min.map_or(true, |min| len >= min),
+    )?;
+    check(
+        value,
+        path,
+        "max length",
+        data.max.map_or(true, |max| len <= max),
+    )?;
+    if let Some(re) = pattern {
+        check(
+            value,
+            path,
+            &format!("pattern {:?}", re.as_str()),
+            re.is_match(s),
+        )?;
+    }
+    if let Some(format) = &data.format {
+        check(
+            value,
+            path,
+            &format!("format {format:?}"),
+            matches_format(format, s),
+        )?;
+    }
+    check_enumeration(value, data.enumeration.as_deref(), path)
+}
+
+impl TypeId {
+    /// Checks a value against the type and its constraints, as the typegate
+    /// would do for an input; the string formats are approximated.
+    #[allow(dead_code)]
+    pub fn validate_value(&self, value: &serde_json::Value) -> Result<()> {
+        validate(value, *self, "<value>".to_string(), true)
+    }
+}
+
+fn check(value: &serde_json::Value, path: &str, constraint: &str, valid: bool) -> Result<()> {
+    match valid {
+        true => Ok(()),
+        false => Err(format!("value {value} at {path:?} does not satisfy the {constraint}").into()),
+    }
+}
+
+/// Checks the membership of a value in an enumeration of JSON-serialized values
+fn check_enumeration(
+    value: &serde_json::Value,
+    enumeration: Option<&[String]>,
+    path: &str,
+) -> Result<()> {
+    let Some(enumeration) = enumeration else {
+        return Ok(());
+    };
+    let found = enumeration
+        .iter()
+        .any(|v| serde_json::from_str::<serde_json::Value>(v).ok().as_ref() == Some(value));
+    check(value, path, "enumeration", found)
+}
+
+fn matches_format(format: &str, value: &str) -> bool {
+    let re: &Regex = match format {
+        "uuid" => &UUID_REGEX,
+        "email" => &EMAIL_REGEX,
+        "uri" => &URI_REGEX,
+        "date" => &DATE_REGEX,
+        "date-time" => &DATE_TIME_REGEX,
+        "json" => return serde_json::from_str::<serde_json::Value>(value).is_ok(),
+        // not checked
+        _ => return true,
+    };
+    re.is_match(value)
+}
+
 pub(super) mod utils {
     use crate::types::TypeId;
 